@@ -1,109 +1,403 @@
-use std::fs::{File, create_dir_all, metadata, remove_file};
-use std::io::{copy};
-use ureq::Agent;
+use std::io::{ErrorKind, SeekFrom};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+use futures_util::{StreamExt, stream};
+use indicatif::ProgressBar;
+use reqwest::StatusCode;
+use reqwest::header::RANGE;
+use tokio::fs::{File, OpenOptions, create_dir_all, metadata, remove_file, rename};
+use tokio::io::{AsyncSeekExt, copy};
+use tokio::task;
 use crate::BINANCE_BIRTH;
-use crate::utils::integrity::check_zip_integrity;
+use crate::utils::download_client::DownloadClient;
+use crate::utils::endpoints::get_local_path;
+use crate::utils::listing::{ListedFile, list_files};
+use crate::utils::integrity::check_zip_checksum;
 use crate::utils::asset_file::{AssetFile};
 use crate::utils::errors::ScrapperError;
-use crate::utils::month_year::MonthYear;
+use crate::utils::month_year::{MonthYear, get_missing_months};
 use crate::utils::process_data::ProcessData;
 
-pub fn download_asset(process: &mut ProcessData, agent: Agent) -> Result<Option<MonthYear>, ScrapperError> {
-    let end_time = process.get_end();
-    let mut start_time = end_time.clone();
-    let mut last_iteration = false;
-    'downloads: for year in (BINANCE_BIRTH..=end_time.get_year()).rev() {
-        let mut max_month = 12;
-        if year == end_time.get_year() {
-            max_month = end_time.get_month();
-        }
-        for month in (1..=max_month).rev() {
-            if last_iteration {
-                break 'downloads;
+//Latest daily archives can be published with a few days of delay
+const MAX_UNPUBLISHED_DAYS: i64 = 7;
+//Consecutive missing monthly archives after which the history is considered over when probing
+const MAX_MISSING_MONTHS: usize = 6;
+
+pub async fn download_asset(process: &mut ProcessData, client: &DownloadClient) -> Result<(), ScrapperError> {
+    match list_asset_files(process, client).await {
+        Ok(listed_archives) => {
+            let listed_files = listed_archives.apply(process);
+            download_listed_files(process, listed_files, client).await
+        }
+        //The listing is only an optimization, archives can still be found by probing them one by one
+        Err(ScrapperError::NetworkError(_) | ScrapperError::ParseError(_)) => probe_asset_files(process, client).await,
+        Err(err) => Err(err),
+    }
+}
+
+//Archives found in the bucket listing, the process is only planned once every listing succeeded so that probing starts clean
+struct ListedArchives {
+    files: Vec<(AssetFile, ListedFile)>,
+    months: Vec<MonthYear>,
+    dates: Vec<NaiveDate>,
+}
+
+impl ListedArchives {
+    //The start and end of the history are the first and last listed archives
+    fn apply(self, process: &mut ProcessData) -> Vec<(AssetFile, ListedFile)> {
+        if let (Some(first_month), Some(last_month)) = (self.months.first(), self.months.last()) {
+            process.set_start(first_month.clone());
+            process.set_end(last_month.clone());
+            for missing_month in get_missing_months(&self.months) {
+                process.add_missing_month(missing_month);
             }
-            if let Some(start) = process.get_start() {
-                if start.get_year() == year && start.get_month() == month {
-                    last_iteration = true;
-                }
+        } else if let Some(first_date) = self.dates.first() {
+            process.set_start(MonthYear::new(first_date.month() as u8, first_date.year()));
+        }
+        for month_year in self.months {
+            process.add_monthly_file(month_year);
+        }
+        for date in self.dates {
+            process.add_daily_file(date);
+        }
+        self.files
+    }
+}
+
+async fn list_asset_files(process: &ProcessData, client: &DownloadClient) -> Result<ListedArchives, ScrapperError> {
+    let mut listed_archives = ListedArchives { files: vec![], months: vec![], dates: vec![] };
+    let mut daily_marker: Option<String> = None;
+    if process.get_data_type().has_monthly_archives() {
+        for listed_file in list_files(client, &process.get_listing_prefix(false), None).await? {
+            if let Some(date) = get_archive_date(&listed_file, false) {
+                let month_year = MonthYear::new(date.month() as u8, date.year());
+                listed_archives.files.push((process.get_asset_file(month_year.clone()), listed_file));
+                listed_archives.months.push(month_year);
+            }
+        }
+        if let Some(last_month) = listed_archives.months.last() {
+            //Daily archives are only needed after the last monthly archive
+            let last_day = last_month.next().get_first_day().pred_opt().unwrap();
+            daily_marker = Some(process.get_listing_prefix(true) + &process.get_daily_asset_file(last_day).get_full_file_name(".zip.CHECKSUM"));
+        }
+    }
+    if process.get_data_type().has_daily_archives() {
+        for listed_file in list_files(client, &process.get_listing_prefix(true), daily_marker.as_deref()).await? {
+            if let Some(date) = get_archive_date(&listed_file, true) {
+                listed_archives.files.push((process.get_daily_asset_file(date), listed_file));
+                listed_archives.dates.push(date);
             }
-            let month_year = MonthYear::new(month, year);
-            let asset_file = AssetFile::new(&process.get_asset(), &process.get_granularity(), month_year.clone());
-            if let Err(err) = download_file(&asset_file, agent.clone()) {
+        }
+    }
+    Ok(listed_archives)
+}
+
+async fn download_listed_files(process: &mut ProcessData, listed_files: Vec<(AssetFile, ListedFile)>, client: &DownloadClient) -> Result<(), ScrapperError> {
+    let planned_size = listed_files.iter().map(|(_, listed_file)| listed_file.size).sum();
+    process.set_progress_bar_plan(listed_files.len() as u64, planned_size);
+    let mut asset_files: Vec<AssetFile> = vec![];
+    for (asset_file, listed_file) in listed_files {
+        //Republished archives are newer than the local copy, it has to be downloaded again
+        let file_path = asset_file.get_download_directory() + &asset_file.get_full_file_name(".zip");
+        if let Ok(modified) = metadata(&file_path).await.and_then(|metadata| metadata.modified()) {
+            if DateTime::<Utc>::from(modified) < listed_file.last_modified {
+                remove_file(&file_path).await?;
+            }
+        }
+        asset_files.push(asset_file);
+    }
+    for result in download_files(&asset_files, client, process.get_progress_bar().as_ref()).await {
+        result?;
+    }
+    Ok(())
+}
+
+//Archive keys end with their date, {symbol}-{dataset}-{yyyy}-{mm}[-{dd}].zip, checksum files are ignored
+fn get_archive_date(listed_file: &ListedFile, daily: bool) -> Option<NaiveDate> {
+    let file_name = listed_file.get_file_name().strip_suffix(".zip")?;
+    let date_length = if daily { 10 } else { 7 };
+    let date = file_name.get(file_name.len().checked_sub(date_length)?..)?;
+    if daily {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    } else {
+        NaiveDate::parse_from_str(&format!("{}-01", date), "%Y-%m-%d").ok()
+    }
+}
+
+//Walks back month by month until the symbol wasn't listed yet, used when the bucket can't be listed
+//Months are probed in batches, results are still read from the newest to the oldest to find the start
+async fn probe_asset_files(process: &mut ProcessData, client: &DownloadClient) -> Result<(), ScrapperError> {
+    if !process.get_data_type().has_monthly_archives() {
+        return download_daily_history(process, client).await;
+    }
+    let mut candidates: Vec<MonthYear> = vec![];
+    let mut month_year = process.get_end();
+    loop {
+        candidates.push(month_year.clone());
+        if process.get_start() == Some(month_year.clone()) || (month_year.get_year() == BINANCE_BIRTH && month_year.get_month() == 1) {
+            break;
+        }
+        month_year = month_year.previous();
+    }
+    let mut months: Vec<MonthYear> = vec![];
+    //Months missing since the last found archive, they are holes only if an older archive is found
+    let mut missing_months: Vec<MonthYear> = vec![];
+    let mut holes: Vec<MonthYear> = vec![];
+    //Delisted symbols stop before the end date, their last available archive becomes the end
+    let mut end_found = !process.is_delisted();
+    'downloads: for batch in candidates.chunks(client.get_pool().get_per_asset()) {
+        let asset_files: Vec<AssetFile> = batch.iter().map(|month_year| process.get_asset_file(month_year.clone())).collect();
+        let results = download_files(&asset_files, client, process.get_progress_bar().as_ref()).await;
+        for (month_year, result) in batch.iter().zip(results) {
+            if let Err(err) = result {
                 match err {
                     ScrapperError::NoOnlineData => {
-                        process.set_start(start_time.clone());
-                        break 'downloads;
+                        if !end_found {
+                            continue;
+                        }
+                        missing_months.push(month_year.clone());
+                        //Without a cached start, a long run of missing months means the symbol wasn't listed yet
+                        if process.get_start().is_none() && missing_months.len() >= MAX_MISSING_MONTHS {
+                            break 'downloads;
+                        }
+                        continue;
                     }
                     _ => {
                         return Err(err);
                     }
                 };
             }
+            if !end_found {
+                end_found = true;
+                process.set_end(month_year.clone());
+            }
 
-            start_time = month_year;
-            process.increment_progress_bar();
+            //An older archive exists, the months missing in between are holes
+            holes.append(&mut missing_months);
+            months.push(month_year.clone());
         }
     }
-    if start_time.get_year() == end_time.get_year() && start_time.get_month() == end_time.get_month() {
-        Ok(None)
-    } else {
-        Ok(Some(start_time))
+    if let Some(first_month) = months.last() {
+        process.set_start(first_month.clone());
+    }
+    for month_year in months.into_iter().rev() {
+        process.add_monthly_file(month_year);
+    }
+    for missing_month in holes.into_iter().rev() {
+        process.add_missing_month(missing_month);
+    }
+    if !process.is_delisted() {
+        download_daily_files(process, client).await?;
+    }
+    Ok(())
+}
+
+//Monthly archives lag behind, the months after the end date are covered by daily archives
+async fn download_daily_files(process: &mut ProcessData, client: &DownloadClient) -> Result<(), ScrapperError> {
+    if !process.get_data_type().has_daily_archives() {
+        return Ok(());
+    }
+    let last_day = Local::now().date_naive().pred_opt().unwrap();
+    let candidates: Vec<NaiveDate> = process.get_end().next().get_first_day().iter_days().take_while(|date| *date <= last_day).collect();
+    for batch in candidates.chunks(client.get_pool().get_per_asset()) {
+        let asset_files: Vec<AssetFile> = batch.iter().map(|date| process.get_daily_asset_file(*date)).collect();
+        for (date, result) in batch.iter().zip(download_files(&asset_files, client, None).await) {
+            if let Err(err) = result {
+                match err {
+                    ScrapperError::NoOnlineData => {
+                        //Before the first available day the asset wasn't listed yet, after it the archive isn't published yet
+                        if !process.get_daily_files().is_empty() {
+                            return Ok(());
+                        }
+                    }
+                    _ => {
+                        return Err(err);
+                    }
+                };
+            } else {
+                process.add_daily_file(*date);
+            }
+        }
+    }
+    Ok(())
+}
+
+//Walks back day by day for data types only published as daily archives
+async fn download_daily_history(process: &mut ProcessData, client: &DownloadClient) -> Result<(), ScrapperError> {
+    let last_day = Local::now().date_naive().pred_opt().unwrap();
+    let first_day = match process.get_start() {
+        Some(start) => start.get_first_day(),
+        None => NaiveDate::from_ymd_opt(BINANCE_BIRTH, 1, 1).unwrap(),
+    };
+    let candidates: Vec<NaiveDate> = last_day.iter_days().rev().take_while(|date| *date >= first_day).collect();
+    let mut dates: Vec<NaiveDate> = vec![];
+    'downloads: for batch in candidates.chunks(client.get_pool().get_per_asset()) {
+        let asset_files: Vec<AssetFile> = batch.iter().map(|date| process.get_daily_asset_file(*date)).collect();
+        for (date, result) in batch.iter().zip(download_files(&asset_files, client, None).await) {
+            if let Err(err) = result {
+                match err {
+                    ScrapperError::NoOnlineData => {
+                        //Delisted symbols are walked back until their last available archive
+                        let unpublished = !process.is_delisted() && (last_day - *date).num_days() >= MAX_UNPUBLISHED_DAYS;
+                        if !dates.is_empty() || unpublished {
+                            break 'downloads;
+                        }
+                    }
+                    _ => {
+                        return Err(err);
+                    }
+                };
+            } else {
+                dates.push(*date);
+            }
+            if date.day() == 1 {
+                process.increment_progress_bar();
+            }
+        }
     }
+    if let Some(first_date) = dates.last() {
+        process.set_start(MonthYear::new(first_date.month() as u8, first_date.year()));
+    }
+    for date in dates.into_iter().rev() {
+        process.add_daily_file(date);
+    }
+    Ok(())
+}
+
+//Downloads up to `per_asset` files at once, results keep the order of the files
+async fn download_files(asset_files: &[AssetFile], client: &DownloadClient, progress_bar: Option<&ProgressBar>) -> Vec<Result<(), ScrapperError>> {
+    //Files are looked up by index, with borrowed items the compiler fails to prove the asset task holding this stream is Send
+    stream::iter(0..asset_files.len())
+        .map(|index| async move {
+            let result = {
+                let _permit = client.get_pool().acquire().await;
+                download_file(&asset_files[index], client).await
+            };
+            if let Some(progress_bar) = progress_bar {
+                progress_bar.inc(1);
+            }
+            result
+        })
+        .buffered(client.get_pool().get_per_asset())
+        .collect()
+        .await
 }
 
-pub fn download_file(asset_file: &AssetFile, agent: Agent) -> Result<(), ScrapperError> {
+//Archives are downloaded next to their final path with a .part suffix, and only moved into place once verified
+pub async fn download_file(asset_file: &AssetFile, client: &DownloadClient) -> Result<(), ScrapperError> {
     let file_path = asset_file.get_download_directory() + &asset_file.get_full_file_name(".zip");
+    let checksum_path = file_path.clone() + ".CHECKSUM";
 
-    if check_zip_integrity(&file_path).is_ok() {
+    if verify_checksum(&file_path, &checksum_path).await.is_ok() {
         return Ok(());
     }
-    download(&asset_file, ".zip", false, agent.clone())?;
-    download(&asset_file, ".zip.CHECKSUM", false, agent.clone())?;
+    download(asset_file, ".zip.CHECKSUM", false, client).await?;
+    download(asset_file, ".zip", true, client).await?;
 
-    if check_zip_integrity(&file_path).is_err() {
-        download(&asset_file, ".zip", true, agent.clone())?;
-        download(&asset_file, ".zip.CHECKSUM", true, agent.clone())?;
+    let part_path = get_part_path(&file_path);
+    let checksum_part_path = get_part_path(&checksum_path);
+    //A corrupted partial download can't be resumed, it is downloaded again from scratch
+    if verify_checksum(&part_path, &checksum_part_path).await.is_err() {
+        remove_file(&part_path).await?;
+        download(asset_file, ".zip", false, client).await?;
     }
-    check_zip_integrity(&file_path)?;
+    verify_checksum(&part_path, &checksum_part_path).await?;
+    rename(&part_path, &file_path).await?;
+    rename(&checksum_part_path, &checksum_path).await?;
     Ok(())
 }
 
-fn download(asset_file: &AssetFile, extension: &str, overwrite: bool, agent: Agent) -> Result<(), ScrapperError> {
-    let file_path = asset_file.get_download_directory() + &asset_file.get_full_file_name(extension);
-    if check_file(&file_path) && overwrite {
-        remove_file(&file_path)?;
+//Hashing an archive is CPU bound, it runs on the blocking pool
+async fn verify_checksum(file_path: &str, checksum_path: &str) -> Result<(), ScrapperError> {
+    let (file_path, checksum_path) = (file_path.to_string(), checksum_path.to_string());
+    task::spawn_blocking(move || check_zip_checksum(&file_path, &checksum_path)).await.unwrap()
+}
+
+//Resumes an existing .part file with a range request, the server may still answer with the whole file
+async fn download(asset_file: &AssetFile, extension: &str, resume: bool, client: &DownloadClient) -> Result<(), ScrapperError> {
+    let part_path = get_part_path(&(asset_file.get_download_directory() + &asset_file.get_full_file_name(extension)));
+    if !resume && check_file(&part_path).await {
+        remove_file(&part_path).await?;
     }
-    let response = agent.get(&asset_file.get_download_url(extension)).call();
+    let url = client.get_endpoints().get_data_url(&asset_file.get_archive_path(extension));
+    client.get_retry_policy().run(|| async {
+        let offset = metadata(&part_path).await.map(|metadata| metadata.len()).unwrap_or(0);
+        create_dir_all(asset_file.get_download_directory()).await?;
 
-    match response {
-        Ok(_) => {}
-        Err(error) => {
-            return match error {
-                ureq::Error::Status(code, _) => {
-                    if code == 404 {
-                        Err(ScrapperError::NoOnlineData)
-                    } else {
-                        Err(ScrapperError::NetworkError(error))
-                    }
-                }
-                ureq::Error::Transport(_) => {
-                    Err(ScrapperError::NetworkError(error))
-                }
+        if let Some(local_path) = get_local_path(&url) {
+            let mut source = match File::open(local_path).await {
+                Ok(source) => source,
+                Err(error) if error.kind() == ErrorKind::NotFound => return Err(ScrapperError::NoOnlineData),
+                Err(error) => return Err(error.into()),
             };
+            source.seek(SeekFrom::Start(offset)).await?;
+            let mut file = OpenOptions::new().create(true).append(true).open(&part_path).await?;
+            copy(&mut source, &mut file).await?;
+            return Ok(());
         }
-    }
-
-    create_dir_all(&asset_file.get_download_directory())?;
 
-    let mut file = File::create(file_path)?;
+        let mut request = client.request(&url).await;
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+        let response = match client.send(request).await {
+            //Nothing left after the offset, the partial file is already complete
+            Err(ScrapperError::NetworkError(error)) if offset > 0 && error.status() == Some(StatusCode::RANGE_NOT_SATISFIABLE) => return Ok(()),
+            response => response?,
+        };
+        let resumed = response.status() == StatusCode::PARTIAL_CONTENT;
+        let mut file = OpenOptions::new().create(true).write(true).append(resumed).truncate(!resumed).open(&part_path).await?;
+        client.copy_body(response, &mut file).await
+    }).await
+}
 
-    copy(&mut response.unwrap().into_reader(), &mut file)?;
-    Ok(())
+fn get_part_path(file_path: &str) -> String {
+    format!("{}.part", file_path)
 }
 
-fn check_file(path: &str) -> bool {
-    if let Ok(metadata) = metadata(path) {
+async fn check_file(path: &str) -> bool {
+    if let Ok(metadata) = metadata(path).await {
         return metadata.is_file();
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs;
+    use crate::utils::data_type::DataType;
+    use crate::utils::endpoints::Endpoints;
+    use crate::utils::kline_schema::KlineSchema;
+    use crate::utils::market::Market;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_local_asset_files() {
+        let root = temp_dir().join("listing_tree");
+        let monthly_directory = root.join("data/spot/monthly/klines/TESTUSDT/1d");
+        let daily_directory = root.join("data/spot/daily/klines/TESTUSDT/1d");
+        fs::create_dir_all(&monthly_directory).unwrap();
+        fs::create_dir_all(&daily_directory).unwrap();
+        for file_name in ["TESTUSDT-1d-2024-01.zip", "TESTUSDT-1d-2024-01.zip.CHECKSUM", "TESTUSDT-1d-2024-03.zip"] {
+            fs::write(monthly_directory.join(file_name), "").unwrap();
+        }
+        for file_name in ["TESTUSDT-1d-2024-03-31.zip", "TESTUSDT-1d-2024-04-01.zip", "TESTUSDT-1d-2024-04-01.zip.CHECKSUM"] {
+            fs::write(daily_directory.join(file_name), "").unwrap();
+        }
+
+        let endpoints = Endpoints::new(Some(format!("file://{}", root.to_string_lossy())), None);
+        let client = DownloadClient::builder(endpoints).build().unwrap();
+        let mut process = ProcessData::new(Market::Spot, DataType::Klines, "1d", KlineSchema::Slim, "TESTUSDT", None, false);
+        let listed_archives = list_asset_files(&process, &client).await.unwrap();
+        let listed_files = listed_archives.apply(&mut process);
+        fs::remove_dir_all(root).unwrap();
+
+        //Checksum files are skipped and daily archives start after the last monthly archive
+        assert_eq!(listed_files.len(), 3);
+        assert_eq!(process.get_monthly_files(), vec![MonthYear::new(1, 2024), MonthYear::new(3, 2024)]);
+        assert_eq!(process.get_missing_months(), vec![MonthYear::new(2, 2024)]);
+        assert_eq!(process.get_daily_files(), vec![NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()]);
+        assert_eq!(process.get_start(), Some(MonthYear::new(1, 2024)));
+        assert_eq!(process.get_end(), MonthYear::new(3, 2024));
+    }
+}
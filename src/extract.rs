@@ -1,57 +1,52 @@
 use std::fs::{File, create_dir_all, remove_file, metadata};
-use std::io::{Read, Write};
-use csv::{ReaderBuilder, StringRecord};
-use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use csv::ReaderBuilder;
 use zip::ZipArchive;
+use crate::records::agg_trade::AggTrade;
+use crate::records::book_depth::BookDepth;
+use crate::records::columns::{ColumnMap, get_headerless_layout};
+use crate::records::book_ticker::BookTicker;
+use crate::records::bvol_index::BvolIndex;
+use crate::records::eoh_summary::EohSummary;
+use crate::records::full_kline::FullKline;
+use crate::records::funding_rate::FundingRate;
+use crate::records::kline::ExtractedData;
+use crate::records::metrics::Metrics;
+use crate::records::trade::Trade;
+use crate::records::Record;
 use crate::utils::asset_file::AssetFile;
+use crate::utils::data_type::DataType;
 use crate::utils::errors::ScrapperError;
+use crate::utils::kline_schema::KlineSchema;
 use crate::utils::manifest::TimePeriod;
-use crate::utils::month_year::MonthYear;
 use crate::utils::process_data::ProcessData;
+use crate::utils::timestamp_unit::TimestampUnit;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ExtractedData {
-    open_time: u64,
-    open: f64,
-    high: f64,
-    low: f64,
-    close: f64,
-    volume: f64,
-}
-
-impl PartialEq for ExtractedData {
-    fn eq(&self, other: &Self) -> bool {
-        self.open_time == other.open_time &&
-            self.open == other.open &&
-            self.high == other.high &&
-            self.low == other.low &&
-            self.close == other.close &&
-            self.volume == other.volume
+pub fn extract_asset(process: &mut ProcessData) -> Result<(Vec<TimePeriod>, TimePeriod), ScrapperError> {
+    match process.get_data_type() {
+        DataType::Klines | DataType::MarkPriceKlines | DataType::IndexPriceKlines | DataType::PremiumIndexKlines => match process.get_schema() {
+            KlineSchema::Slim => extract_records::<ExtractedData>(process),
+            KlineSchema::Full => extract_records::<FullKline>(process),
+        },
+        DataType::AggTrades => stream_records::<AggTrade>(process),
+        DataType::Trades => stream_records::<Trade>(process),
+        DataType::FundingRate => extract_records::<FundingRate>(process),
+        DataType::Metrics => extract_records::<Metrics>(process),
+        DataType::BookTicker => stream_records::<BookTicker>(process),
+        DataType::BookDepth => extract_records::<BookDepth>(process),
+        DataType::EohSummary => extract_records::<EohSummary>(process),
+        DataType::BvolIndex => extract_records::<BvolIndex>(process),
     }
 }
 
-pub fn extract_asset(process: &mut ProcessData, start_time: MonthYear) -> Result<(Vec<TimePeriod>, TimePeriod), ScrapperError> {
-    let end_time = process.get_end();
-
-    let global_asset_file = AssetFile::new(&process.get_asset(), &process.get_granularity(), start_time.clone());
+fn extract_records<T: Record>(process: &mut ProcessData) -> Result<(Vec<TimePeriod>, TimePeriod), ScrapperError> {
+    let global_asset_file = process.get_asset_file(process.get_end());
 
     init_result_file(&global_asset_file)?;
-    let mut extracted_data: Vec<ExtractedData> = vec![];
+    let mut extracted_data: Vec<T> = vec![];
 
-    for year in start_time.get_year()..=end_time.get_year() {
-        let max_month = if year == end_time.get_year() {
-            end_time.get_month()
-        } else {
-            12
-        };
-        let min_month = if year == start_time.get_year() {
-            start_time.get_month()
-        } else { 1 };
-        for month in min_month..=max_month {
-            let month_year = MonthYear::new(month, year);
-            let asset_file = AssetFile::new(&process.get_asset(), &process.get_granularity(), month_year.clone());
-            extracted_data.extend(extract_file(&asset_file)?);
-        }
+    for asset_file in get_asset_files(process) {
+        extracted_data.extend(extract_file(&asset_file)?);
     }
     let asset_data = post_treatment(&global_asset_file, &mut extracted_data)?;
     save_file(&global_asset_file, &extracted_data)?;
@@ -59,40 +54,106 @@ pub fn extract_asset(process: &mut ProcessData, start_time: MonthYear) -> Result
     Ok(asset_data)
 }
 
-pub fn extract_file(asset_file: &AssetFile) -> Result<Vec<ExtractedData>, ScrapperError> {
-    let source_path = asset_file.get_download_directory() + &asset_file.get_full_file_name(".zip");
-    let source_file = File::open(source_path.clone())?;
-
-    let mut archive = ZipArchive::new(source_file)?;
-
-    let mut entry = archive.by_index(0)?;
-
-    let mut csv_content = String::new();
-    entry.read_to_string(&mut csv_content)?;
+//Records are written one by one as a sequence of bincode entries, so that a month is never held in memory
+fn stream_records<T: Record>(process: &mut ProcessData) -> Result<(Vec<TimePeriod>, TimePeriod), ScrapperError> {
+    let global_asset_file = process.get_asset_file(process.get_end());
 
-    let mut csv_reader = ReaderBuilder::new().has_headers(false).from_reader(csv_content.as_bytes());
+    init_result_file(&global_asset_file)?;
+    let mut writer = BufWriter::new(File::create(global_asset_file.get_result_file_path())?);
+    let mut start_ts: Option<u64> = None;
+    let mut last_ts = 0;
+    let mut records_count: u64 = 0;
 
-    let mut extracted_records: Vec<ExtractedData> = vec![];
+    for asset_file in get_asset_files(process) {
+        read_file(&asset_file, |record: T| {
+            let ts = record.get_timestamp();
+            if ts < last_ts {
+                return Err(ScrapperError::IntegrityError("Timestamps are not in the wright order:".to_string()));
+            }
+            start_ts.get_or_insert(ts);
+            last_ts = ts;
+            records_count += 1;
+            bincode::serialize_into(&mut writer, &record)?;
+            Ok(())
+        })?;
+    }
+    writer.flush()?;
+    let start_ts = start_ts.ok_or(ScrapperError::IntegrityError("No data found in extracted_data".to_string()))?;
+    check_streamed_file_integrity::<T>(&global_asset_file.get_result_file_path(), records_count)?;
+    Ok((vec![], TimePeriod::new(start_ts, last_ts)))
+}
 
-    for csv_record in csv_reader.records() {
-        let record = csv_record?;
-        extracted_records.push(extract_record(record)?);
+pub fn get_asset_files(process: &ProcessData) -> Vec<AssetFile> {
+    let mut asset_files: Vec<AssetFile> = vec![];
+    for month_year in process.get_monthly_files() {
+        asset_files.push(process.get_asset_file(month_year));
+    }
+    for date in process.get_daily_files() {
+        asset_files.push(process.get_daily_asset_file(date));
     }
+    asset_files
+}
+
+pub fn extract_file<T: Record>(asset_file: &AssetFile) -> Result<Vec<T>, ScrapperError> {
+    let mut extracted_records: Vec<T> = vec![];
+    read_file(asset_file, |record| {
+        extracted_records.push(record);
+        Ok(())
+    })?;
     Ok(extracted_records)
 }
 
+fn read_file<T: Record, F: FnMut(T) -> Result<(), ScrapperError>>(asset_file: &AssetFile, mut on_record: F) -> Result<(), ScrapperError> {
+    let source_path = asset_file.get_download_directory() + &asset_file.get_full_file_name(".zip");
+    let source_file = File::open(source_path.clone())?;
+
+    let mut archive = ZipArchive::new(source_file)?;
+
+    let entry = archive.by_index(0)?;
+
+    let mut csv_reader = ReaderBuilder::new().has_headers(false).flexible(true).from_reader(entry);
 
-fn extract_record(record: StringRecord) -> Result<ExtractedData, ScrapperError> {
-    let collected_record: Vec<&str> = record.iter().collect();
+    let mut column_map: Option<ColumnMap> = None;
+    let mut timestamp_unit: Option<TimestampUnit> = None;
+    for csv_record in csv_reader.records() {
+        let csv_record = csv_record?;
+        let line = csv_record.position().map_or(0, |position| position.line());
+        let layout_error = |reason: String| ScrapperError::ParseError(format!("Unrecognized columns layout in {} at line {}, {}", source_path, line, reason));
 
-    //Format based on https://github.com/binance/binance-public-data/
-    let open_time = collected_record[0].parse::<u64>()?;
-    let open = collected_record[1].parse::<f64>()?;
-    let high = collected_record[2].parse::<f64>()?;
-    let low = collected_record[3].parse::<f64>()?;
-    let close = collected_record[4].parse::<f64>()?;
-    let volume = collected_record[5].parse::<f64>()?;
-    Ok(ExtractedData { open_time, open, high, low, close, volume })
+        let column_map = match column_map {
+            Some(ref column_map) => column_map,
+            None => {
+                //Some archives ship a header row, the others follow the known layout of their data type
+                let header = ColumnMap::is_header(&csv_record, T::COLUMNS);
+                let names: Vec<&str> = if header {
+                    csv_record.iter().collect()
+                } else {
+                    get_headerless_layout(asset_file.get_data_type(), asset_file.get_market())
+                        .ok_or_else(|| layout_error("no header row and no known layout".to_string()))?
+                        .to_vec()
+                };
+                let new_map = ColumnMap::new(&names, T::COLUMNS)
+                    .map_err(|column| layout_error(format!("missing column {}", column)))?;
+                let column_map = column_map.insert(new_map);
+                if header {
+                    continue;
+                }
+                column_map
+            }
+        };
+        let values = column_map.select(&csv_record)
+            .ok_or_else(|| layout_error(format!("only {} columns found", csv_record.len())))?;
+        let mut record = T::from_csv(&values).map_err(|err| match err {
+            ScrapperError::ParseError(msg) => ScrapperError::ParseError(format!("{} in {} at line {}", msg, source_path, line)),
+            _ => err,
+        })?;
+        let unit = *timestamp_unit.get_or_insert(TimestampUnit::detect(record.get_timestamp()));
+        if unit == TimestampUnit::Microseconds {
+            record.to_milliseconds();
+        }
+        on_record(record)?;
+    }
+    Ok(())
 }
 
 pub fn init_result_file(asset_file: &AssetFile) -> Result<(), ScrapperError> {
@@ -104,16 +165,16 @@ pub fn init_result_file(asset_file: &AssetFile) -> Result<(), ScrapperError> {
     Ok(())
 }
 
-pub fn post_treatment(asset_file: &AssetFile, extracted_data: &mut Vec<ExtractedData>) -> Result<(Vec<TimePeriod>, TimePeriod), ScrapperError> {
-    extracted_data.sort_by(|a, b| a.open_time.cmp(&b.open_time));
-    let start_ts = extracted_data.get(0)
+pub fn post_treatment<T: Record>(asset_file: &AssetFile, extracted_data: &mut [T]) -> Result<(Vec<TimePeriod>, TimePeriod), ScrapperError> {
+    extracted_data.sort_by_key(|a| a.get_timestamp());
+    let start_ts = extracted_data.first()
         .ok_or(ScrapperError::IntegrityError("No data found in extracted_data".to_string()))?
-        .open_time;
+        .get_timestamp();
     let mut last_ts = 0;
     let mut down_periods: Vec<TimePeriod> = vec![];
 
     for entry in extracted_data {
-        let ts = entry.open_time;
+        let ts = entry.get_timestamp();
 
         if last_ts == 0 {
             last_ts = ts;
@@ -121,19 +182,25 @@ pub fn post_treatment(asset_file: &AssetFile, extracted_data: &mut Vec<Extracted
         if ts < last_ts {
             return Err(ScrapperError::IntegrityError("Timestamps are not in the wright order:".to_string()));
         }
-        if ts - last_ts > asset_file.get_ts_factor() || !is_multiple_of_granularity(ts, asset_file.get_ts_factor()) {
-            let down_period = TimePeriod::new(last_ts, ts);
-            down_periods.push(down_period);
+        //Only fixed interval data can have down times
+        if let Some(ts_factor) = asset_file.get_ts_factor() {
+            if ts - last_ts > ts_factor || !is_multiple_of_granularity(ts, ts_factor) {
+                let down_period = TimePeriod::new(last_ts, ts);
+                down_periods.push(down_period);
+            }
         }
         last_ts = ts;
     }
     Ok((down_periods, TimePeriod::new(start_ts, last_ts)))
 }
 
-fn save_file(asset_file: &AssetFile, extracted_data: &Vec<ExtractedData>) -> Result<(), ScrapperError> {
+fn save_file<T: Record>(asset_file: &AssetFile, extracted_data: &Vec<T>) -> Result<(), ScrapperError> {
+    write_records(&asset_file.get_result_file_path(), extracted_data)
+}
+
+fn write_records<T: Record>(path: &str, extracted_data: &Vec<T>) -> Result<(), ScrapperError> {
     let encoded_data = bincode::serialize(&extracted_data)?;
 
-    let path = asset_file.get_result_file_path();
     let mut file = File::create(path)?;
     file.write_all(&encoded_data)?;
     file.flush()?;
@@ -141,16 +208,16 @@ fn save_file(asset_file: &AssetFile, extracted_data: &Vec<ExtractedData>) -> Res
 }
 
 fn is_multiple_of_granularity(timestamp: u64, factor: u64) -> bool {
-    timestamp % factor == 0
+    timestamp.is_multiple_of(factor)
 }
 
-fn check_file_integrity(path: &str, reference_data: Vec<ExtractedData>) -> Result<(), ScrapperError> {
+fn check_file_integrity<T: Record>(path: &str, reference_data: Vec<T>) -> Result<(), ScrapperError> {
     let mut file = File::open(path)?;
 
     let mut encoded_data = Vec::new();
     file.read_to_end(&mut encoded_data)?;
 
-    let data: Vec<ExtractedData> = bincode::deserialize(&encoded_data)?;
+    let data: Vec<T> = bincode::deserialize(&encoded_data)?;
 
     for (i, entry) in data.iter().enumerate() {
         if entry != &reference_data[i] {
@@ -159,3 +226,41 @@ fn check_file_integrity(path: &str, reference_data: Vec<ExtractedData>) -> Resul
     }
     Ok(())
 }
+
+fn check_streamed_file_integrity<T: Record>(path: &str, records_count: u64) -> Result<(), ScrapperError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut read_count: u64 = 0;
+
+    loop {
+        match bincode::deserialize_from::<_, T>(&mut reader) {
+            Ok(_) => read_count += 1,
+            Err(err) => match *err {
+                bincode::ErrorKind::Io(ref io_err) if io_err.kind() == ErrorKind::UnexpectedEof => break,
+                _ => return Err(err.into()),
+            }
+        }
+    }
+    if read_count != records_count {
+        return Err(ScrapperError::IntegrityError("Data integrity check failed".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use super::*;
+
+    #[test]
+    fn test_eoh_summary_integrity() {
+        let row = "2023-10-19,0,BTC-231020-27000-C,C,27000,,,,,0,0,1.5,,2.1,,0.35,,1.42,0.4,0.62,0.00032,3.2,-40.1,12.5,";
+        let values: Vec<&str> = row.split(',').collect();
+        let records = vec![EohSummary::from_csv(&values).unwrap()];
+
+        let path = temp_dir().join("eoh_summary_integrity.bin").to_string_lossy().to_string();
+        write_records(&path, &records).unwrap();
+        let reference_data = vec![EohSummary::from_csv(&values).unwrap()];
+        assert!(check_file_integrity(&path, reference_data).is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
+}
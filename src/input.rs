@@ -1,6 +1,19 @@
-use std::{env};
+use std::env;
+use std::thread::available_parallelism;
+use std::time::Duration;
 use serde_json::Value;
-use crate::utils::asset_file::STABLE_COIN;
+use tokio::fs;
+use crate::utils::asset_file::{AssetFile, DEFAULT_QUOTE};
+use crate::utils::download_cache::{CachePolicy, DownloadCache};
+use crate::utils::download_client::DownloadClient;
+use crate::utils::download_pool::DownloadPool;
+use crate::utils::endpoints::{Endpoints, get_local_path};
+use crate::utils::listing::list_files;
+use crate::utils::data_type::{DATA_TYPES, DataType};
+use crate::utils::kline_schema::{KLINE_SCHEMAS, KlineSchema};
+use crate::utils::rate_limiter::RateLimiter;
+use crate::utils::retry_policy::RetryPolicy;
+use crate::utils::market::{COIN_M_CONTRACT_TYPES, Market, MARKETS};
 
 pub const GRANULARITIES: [(&str, u64); 13] = [
     ("1s", 1_000),
@@ -17,21 +30,63 @@ pub const GRANULARITIES: [(&str, u64); 13] = [
     ("12h", 12 * 60 * 60_000),
     ("1d", 24 * 60 * 60_000)];
 
+const DEFAULT_PARALLEL_ASSETS: usize = 4;
+
 pub struct Settings {
+    pub market: Market,
+    pub data_type: DataType,
     pub granularity: String,
-    pub assets: Vec<String>,
+    pub schema: KlineSchema,
+    pub symbols: Vec<String>,
+    pub delisted_symbols: Vec<String>,
+    pub client: DownloadClient,
+    pub parallel_assets: usize,
+    pub parallel_extractions: usize,
+    pub download_cache: DownloadCache,
 }
 
-pub fn process_input() -> Settings {
+pub async fn process_input() -> Settings {
     let args: Vec<String> = env::args().collect();
+    let client = build_client(&args);
+    let parallel_assets = check_positive("parallel_assets", get_optional_flag(&args, "parallel_assets")).unwrap_or(DEFAULT_PARALLEL_ASSETS as u64) as usize;
+    //Extraction is CPU bound, one per core by default
+    let parallel_extractions = check_positive("parallel_extractions", get_optional_flag(&args, "parallel_extractions"))
+        .map(|count| count as usize)
+        .unwrap_or(available_parallelism().map(|count| count.get()).unwrap_or(1));
+    let download_cache = DownloadCache::new(check_cache_policy(&args));
+    let market = check_market(&get_flag(&args, "market", "spot"));
+
+    let data_type = check_data_type(market, &get_flag(&args, "data_type", "klines"));
+
     let granularity = get_flag(&args, "granularity", "1m");
-    check_granularity(&granularity);
+    if data_type.has_granularity() {
+        check_granularity(market, &granularity);
+    }
+    let schema = check_schema(&get_flag(&args, "schema", "slim"));
+
+    let include_delisted = has_flag(&args, "include_delisted");
 
-    let asset_input = get_flag(&args, "asset", "everything");
-    let assets = check_asset(&asset_input);
+    let symbol_input = get_flag(&args, "symbol", "");
+    //Delisted symbols are found while checking the selection, from the same exchange information
+    let (symbols, delisted_symbols, selection) = if symbol_input.is_empty() {
+        let quote = get_flag(&args, "quote", DEFAULT_QUOTE).to_uppercase();
+
+        let asset_input = get_flag(&args, "asset", "everything");
+        let (assets, delisted_symbols) = check_asset(&client, market, data_type, &granularity, &quote, &asset_input, include_delisted).await;
+        let symbols = assets.iter()
+            .map(|asset| market.get_symbol(data_type, asset, &quote))
+            .collect();
+        (symbols, delisted_symbols, format!("quote: {} and assets: {}", quote, asset_input))
+    } else {
+        let (symbols, delisted_symbols) = check_full_symbols(&client, market, data_type, &granularity, &symbol_input).await;
+        (symbols, delisted_symbols, format!("symbols: {}", symbol_input))
+    };
+    if !delisted_symbols.is_empty() {
+        println!("Delisted symbols, scrapped up to their last available archive: {:?}", delisted_symbols);
+    }
 
 
-    println!("Processing on granularity: {} and assets: {}, should we continue ? (Y/n)", granularity, asset_input);
+    println!("Processing {} on market: {}, granularity: {}, {}, should we continue ? (Y/n)", data_type.get_name(), market.get_name(), granularity, selection);
     let mut input = String::new();
     std::io::stdin().read_line(&mut input).unwrap();
     if input.trim() != "Y" && input.trim() != "y" {
@@ -39,13 +94,52 @@ pub fn process_input() -> Settings {
     }
 
     Settings {
+        market,
+        data_type,
         granularity,
-        assets,
+        schema,
+        symbols,
+        delisted_symbols,
+        client,
+        parallel_assets,
+        parallel_extractions,
+        download_cache,
     }
 }
 
-fn get_flag(args: &Vec<String>, name: &str, default: &str) -> String {
-    let flag = args.iter().position(|arg| arg == name);
+//Every request of the run goes through this client, including the exchange information ones
+fn build_client(args: &[String]) -> DownloadClient {
+    let endpoints = Endpoints::new(get_optional_flag(args, "data_url"), get_optional_flag(args, "api_url"));
+
+    let parallel_downloads = check_download_count("parallel_downloads", &get_flag(args, "parallel_downloads", "4"));
+    let max_downloads = check_download_count("max_downloads", &get_flag(args, "max_downloads", "16"));
+
+    let retries = get_flag(args, "retries", "3").parse::<u32>().unwrap_or_else(|_| panic!("Invalid retries, should be a number of attempts"));
+
+    let requests_per_second = check_positive("requests_per_second", get_optional_flag(args, "requests_per_second"));
+    let bandwidth = check_positive("bandwidth", get_optional_flag(args, "bandwidth"));
+
+    let mut builder = DownloadClient::builder(endpoints)
+        .pool(DownloadPool::new(parallel_downloads, max_downloads))
+        .retry_policy(RetryPolicy::new(retries))
+        .request_limiter(RateLimiter::new(requests_per_second))
+        .bandwidth_limiter(RateLimiter::new(bandwidth))
+        .proxy(get_optional_flag(args, "proxy"))
+        .ca_bundle(get_optional_flag(args, "ca_bundle"));
+    if let Some(connect_timeout) = check_positive("connect_timeout", get_optional_flag(args, "connect_timeout")) {
+        builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+    }
+    if let Some(timeout) = check_positive("timeout", get_optional_flag(args, "timeout")) {
+        builder = builder.timeout(Duration::from_secs(timeout));
+    }
+    if let Some(user_agent) = get_optional_flag(args, "user_agent") {
+        builder = builder.user_agent(user_agent);
+    }
+    builder.build().unwrap_or_else(|err| panic!("Invalid http client configuration: {}", err))
+}
+
+fn get_flag(args: &[String], name: &str, default: &str) -> String {
+    let flag = args.iter().position(|arg| arg == name || arg.strip_prefix("--") == Some(name));
     if let Some(index) = flag {
         if let Some(value) = args.get(index + 1) {
             return value.to_string();
@@ -54,73 +148,236 @@ fn get_flag(args: &Vec<String>, name: &str, default: &str) -> String {
     default.to_string()
 }
 
-fn check_granularity(granularity: &str) {
+fn get_optional_flag(args: &[String], name: &str) -> Option<String> {
+    Some(get_flag(args, name, "")).filter(|value| !value.is_empty())
+}
+
+fn has_flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|arg| arg == name || arg.strip_prefix("--") == Some(name))
+}
+
+fn check_market(market: &str) -> Market {
+    if let Some(market) = Market::from_name(market) {
+        return market;
+    }
+    panic!("Invalid market, should be one of those {:?}", MARKETS.map(|(key, _)| key));
+}
+
+fn check_data_type(market: Market, data_type: &str) -> DataType {
+    if let Some(data_type) = DataType::from_name(data_type) {
+        if !data_type.is_available_on(market) {
+            panic!("Data type {} is not available on market {}", data_type.get_name(), market.get_name());
+        }
+        return data_type;
+    }
+    panic!("Invalid data type, should be one of those {:?}", DATA_TYPES.map(|(key, _)| key));
+}
+
+fn check_granularity(market: Market, granularity: &str) {
     if !GRANULARITIES.iter().any(|&(key, _)| key == granularity) {
         panic!("Invalid granularity, should be one of those {:?}", GRANULARITIES);
     }
+    if !market.supports_granularity(granularity) {
+        panic!("Granularity {} is not available on market {}", granularity, market.get_name());
+    }
+}
+
+fn check_schema(schema: &str) -> KlineSchema {
+    if let Some(schema) = KlineSchema::from_name(schema) {
+        return schema;
+    }
+    panic!("Invalid schema, should be one of those {:?}", KLINE_SCHEMAS.map(|(key, _)| key));
+}
+
+fn check_download_count(name: &str, count: &str) -> usize {
+    match count.parse::<usize>() {
+        Ok(count) if count > 0 => count,
+        _ => panic!("Invalid {}, should be a positive number of downloads", name),
+    }
+}
+
+//The maximum size is given in GB
+fn check_cache_policy(args: &[String]) -> CachePolicy {
+    let max_cache_size = check_positive("max_cache_size", get_optional_flag(args, "max_cache_size"));
+    match (has_flag(args, "clear_cache"), max_cache_size) {
+        (true, Some(_)) => panic!("Invalid cache policy, clear_cache and max_cache_size can't be used together"),
+        (true, None) => CachePolicy::Clear,
+        (false, Some(max_cache_size)) => CachePolicy::MaxSize(max_cache_size * 1_000_000_000),
+        (false, None) => CachePolicy::Keep,
+    }
 }
 
-fn check_asset(asset: &str) -> Vec<String> {
+//Rates and timeouts are positive numbers, per second or in seconds
+fn check_positive(name: &str, value: Option<String>) -> Option<u64> {
+    let value = value?;
+    match value.parse::<u64>() {
+        Ok(value) if value > 0 => Some(value),
+        _ => panic!("Invalid {}, should be a positive number", name),
+    }
+}
+
+//Returns the selected assets and the full symbols of the delisted ones
+async fn check_asset(client: &DownloadClient, market: Market, data_type: DataType, granularity: &str, quote: &str, asset: &str, include_delisted: bool) -> (Vec<String>, Vec<String>) {
     if asset.contains("everything") {
-        return get_all_assets().unwrap();
+        return get_all_assets(client, market, quote, include_delisted).await
+            .unwrap_or_else(|| panic!("Couldn't read the exchange information of market {}", market.get_name()));
     };
-    if let Some(assets) = check_symbol(asset.to_string()) {
-        return vec![assets];
+    if let Some((asset, delisted)) = check_symbol(client, market, data_type, granularity, quote, asset.to_string()).await {
+        let delisted_symbols = if delisted { vec![market.get_symbol(data_type, &asset, quote)] } else { vec![] };
+        return (vec![asset], delisted_symbols);
     };
     panic!("Invalid asset, let blank to scrap everything");
 }
 
 
-fn check_symbol(asset: String) -> Option<String> {
+//The asset and whether its symbol is delisted, options underlyings are always listed
+async fn check_symbol(client: &DownloadClient, market: Market, data_type: DataType, granularity: &str, quote: &str, asset: String) -> Option<(String, bool)> {
     if asset.is_empty() {
         return None;
     }
-    let url = format!("https://api.binance.com/api/v3/exchangeInfo?symbol={}{}", asset, STABLE_COIN);
-    match ureq::get(&url).call() {
-        Ok(res) => {
-            if res.status() == 200 {
-                return Some(asset.to_string());
-            }
-        }
-        _ => {
-            return None;
+    if market == Market::Options {
+        return get_option_underlyings(client, quote).await?.contains(&asset).then_some((asset, false));
+    }
+    let symbol = market.get_symbol(data_type, &asset, quote);
+    let symbols_array = get_exchange_symbols(client, market, Some(&symbol)).await?;
+    match symbols_array.iter().find(|entry| entry.get("symbol").and_then(|value| value.as_str()) == Some(symbol.as_str())) {
+        Some(entry) => {
+            let delisted = entry.get(market.get_status_field())?.as_str()? != "TRADING";
+            Some((asset, delisted))
         }
+        None => is_delivered_contract(client, market, data_type, granularity, &symbol).await.then_some((asset, true)),
     }
-    None
 }
 
-fn get_all_assets() -> Option<Vec<String>> {
-    let response = ureq::get("https://api.binance.com/api/v3/exchangeInfo").call().ok()?;
-    if response.status() != 200 {
-        return None;
+//Delivered COIN-M contracts leave the exchange information, their archives are still listed in the bucket
+async fn is_delivered_contract(client: &DownloadClient, market: Market, data_type: DataType, granularity: &str, symbol: &str) -> bool {
+    if market != Market::CoinM {
+        return false;
     }
-    let payload = response.into_string().ok()?;
+    let prefix = AssetFile::get_listing_prefix_from_values(market, data_type, symbol, granularity, !data_type.has_monthly_archives());
+    list_files(client, &prefix, None).await.is_ok_and(|listed_files| !listed_files.is_empty())
+}
 
-    let parsed_data: Value = serde_json::from_str(&payload).ok()?;
+//Full symbols are used verbatim, for pairs that can't be expressed as an asset and a quote (ETHBTC, BNBETH)
+async fn check_full_symbols(client: &DownloadClient, market: Market, data_type: DataType, granularity: &str, symbol_input: &str) -> (Vec<String>, Vec<String>) {
+    let mut symbols: Vec<String> = vec![];
+    let mut delisted_symbols: Vec<String> = vec![];
+    for symbol in symbol_input.split(',').map(|symbol| symbol.trim().to_uppercase()) {
+        match get_symbol_pair(client, market, &symbol).await {
+            Some((base_asset, quote_asset, delisted)) => {
+                println!("Symbol {} found with base asset {} and quote asset {}", symbol, base_asset, quote_asset);
+                //Option archives of some data types aren't named after the underlying (BTCBVOLUSDT for BTCUSDT)
+                let symbol = if market == Market::Options { market.get_symbol(data_type, &base_asset, &quote_asset) } else { symbol };
+                if delisted {
+                    delisted_symbols.push(symbol.clone());
+                }
+                symbols.push(symbol);
+            }
+            None if is_delivered_contract(client, market, data_type, granularity, &symbol).await => {
+                println!("Symbol {} found in the archives, its contract is delivered", symbol);
+                delisted_symbols.push(symbol.clone());
+                symbols.push(symbol);
+            }
+            None => panic!("Invalid symbol {}, it isn't listed on market {}", symbol, market.get_name()),
+        }
+    }
+    (symbols, delisted_symbols)
+}
 
-    let symbols_array = parsed_data.get("symbols")?.as_array()?;
+//Base asset, quote asset and whether the symbol is delisted, options contracts have no status
+async fn get_symbol_pair(client: &DownloadClient, market: Market, symbol: &str) -> Option<(String, String, bool)> {
+    //Options archives are named after the underlying of the contracts
+    let symbol_field = if market == Market::Options { "underlying" } else { "symbol" };
+    let symbols_array = get_exchange_symbols(client, market, Some(symbol)).await?;
+    let entry = symbols_array.iter().find(|entry| entry.get(symbol_field).and_then(|value| value.as_str()) == Some(symbol))?;
+    let base_asset = entry.get("baseAsset")?.as_str()?.to_string();
+    let quote_asset = entry.get("quoteAsset")?.as_str()?.to_string();
+    let delisted = market != Market::Options && entry.get(market.get_status_field())?.as_str()? != "TRADING";
+    Some((base_asset, quote_asset, delisted))
+}
 
-    let usdc_compatible_assets = extract_usdc_compatible(symbols_array).unwrap();
+async fn get_all_assets(client: &DownloadClient, market: Market, quote: &str, include_delisted: bool) -> Option<(Vec<String>, Vec<String>)> {
+    if market == Market::Options {
+        return Some((get_option_underlyings(client, quote).await?, vec![]));
+    }
+    let symbols_array = get_exchange_symbols(client, market, None).await?;
+
+    let usdc_compatible_assets = extract_usdc_compatible(&symbols_array).unwrap();
     let mut asset_pairs: Vec<String> = Vec::new();
-    for symbol in symbols_array {
-        let status = symbol.get("status")?.as_str()?.to_string();
-        if status != "TRADING" {
+    let mut delisted_symbols: Vec<String> = Vec::new();
+    for symbol in &symbols_array {
+        let trading = symbol.get(market.get_status_field())?.as_str()? == "TRADING";
+        if !trading && !include_delisted {
+            continue;
+        }
+        if market == Market::CoinM {
+            let contract_type = symbol.get("contractType")?.as_str()?;
+            if COIN_M_CONTRACT_TYPES.contains(&contract_type) {
+                asset_pairs.push(symbol.get("symbol")?.as_str()?.to_string());
+                if !trading {
+                    delisted_symbols.push(symbol.get("symbol")?.as_str()?.to_string());
+                }
+            }
             continue;
         }
         let quote_asset = symbol.get("quoteAsset")?.as_str()?.to_string();
-        if quote_asset != STABLE_COIN {
+        if quote_asset != quote {
+            continue;
+        }
+        if market == Market::UsdM && symbol.get("contractType")?.as_str()? != "PERPETUAL" {
             continue;
         }
         let base_asset = symbol.get("baseAsset")?.as_str()?.to_string();
-        if usdc_compatible_assets.contains(&base_asset) {
+        //Spot USDT pairs are narrowed to bases also quoted in USDC, other quotes are scrapped in full
+        //Delisted assets rarely have a USDC pair, they would all be filtered out
+        if market != Market::Spot || quote != DEFAULT_QUOTE || !trading || usdc_compatible_assets.contains(&base_asset) {
             asset_pairs.push(base_asset);
+            if !trading {
+                delisted_symbols.push(symbol.get("symbol")?.as_str()?.to_string());
+            }
+        }
+    }
+
+    Some((asset_pairs, delisted_symbols))
+}
+
+async fn get_option_underlyings(client: &DownloadClient, quote: &str) -> Option<Vec<String>> {
+    let contracts_array = get_exchange_symbols(client, Market::Options, None).await?;
+
+    let mut underlyings: Vec<String> = Vec::new();
+    for contract in &contracts_array {
+        if contract.get("quoteAsset")?.as_str()? != quote {
+            continue;
+        }
+        let base_asset = contract.get("baseAsset")?.as_str()?.to_string();
+        if !underlyings.contains(&base_asset) {
+            underlyings.push(base_asset);
         }
     }
+    Some(underlyings)
+}
+
+//Spot accepts a symbol filter, futures endpoints always return every symbol
+async fn get_exchange_symbols(client: &DownloadClient, market: Market, symbol: Option<&str>) -> Option<Vec<Value>> {
+    let url = match (market, symbol) {
+        (Market::Spot, Some(symbol)) => format!("{}?symbol={}", client.get_endpoints().get_exchange_info_url(market), symbol),
+        _ => client.get_endpoints().get_exchange_info_url(market),
+    };
+    //A local exchange info file always holds every symbol, the filter is applied by the callers
+    let payload = match get_local_path(&client.get_endpoints().get_exchange_info_url(market)) {
+        Some(local_path) => fs::read_to_string(local_path).await.ok()?,
+        None => client.get_retry_policy().run(|| async {
+            let response = client.send(client.request(&url).await).await?;
+            client.read_to_string(response).await
+        }).await.ok()?,
+    };
+
+    let parsed_data: Value = serde_json::from_str(&payload).ok()?;
 
-    Some(asset_pairs)
+    parsed_data.get(market.get_symbols_field())?.as_array().cloned()
 }
 
-fn extract_usdc_compatible(json: &Vec<Value>) -> Option<Vec<String>> {
+fn extract_usdc_compatible(json: &[Value]) -> Option<Vec<String>> {
     let mut assets: Vec<String> = Vec::new();
     for symbol in json {
         let quote_asset = symbol.get("quoteAsset")?.as_str()?.to_string();
@@ -1,18 +1,22 @@
 mod input;
 mod download;
 mod extract;
+mod records;
 mod utils;
 
+use std::collections::HashMap;
 use std::sync::{Arc, mpsc, Mutex};
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::Sender;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use ureq::{Agent};
 use crate::download::{download_asset};
-use crate::extract::{extract_asset};
+use crate::extract::{extract_asset, get_asset_files};
 use crate::input::Settings;
+use crate::utils::download_cache::DownloadCache;
+use crate::utils::download_client::DownloadClient;
 use crate::utils::errors::ScrapperError;
 use crate::utils::manifest::{Manifest, TimePeriod};
 use crate::utils::process_data::ProcessData;
+use crate::utils::retry_policy::get_backoff_delay;
 use tokio::task;
 use tokio::sync::Semaphore;
 use crate::utils::month_year::MonthYear;
@@ -24,25 +28,40 @@ struct ProcessResult {
     down_times: Vec<TimePeriod>,
     time_period: TimePeriod,
     start_date: Option<MonthYear>,
+    missing_months: Vec<MonthYear>,
+}
+
+//Shared by every asset, downloads and extractions are bounded separately
+#[derive(Clone)]
+struct Pipeline {
+    download_semaphore: Arc<Semaphore>,
+    extract_semaphore: Arc<Semaphore>,
+    download_cache: DownloadCache,
 }
 
 #[tokio::main]
 async fn main() {
-    let settings = input::process_input();
+    let settings = input::process_input().await;
     handle_processes(settings).await;
-    println!("Scrapping completed, you can find your output in 'results' directory");
+    println!("Scrapping completed, you can find your output in 'binance_data/output' directory");
 }
 
 async fn handle_processes(settings: Settings) {
     let multi_progress = MultiProgress::new();
-    let semaphore = Arc::new(Semaphore::new(4));
+    //Assets are downloaded and extracted in separate stages, so that a download never waits for an extraction
+    let pipeline = Pipeline {
+        download_semaphore: Arc::new(Semaphore::new(settings.parallel_assets)),
+        extract_semaphore: Arc::new(Semaphore::new(settings.parallel_extractions)),
+        download_cache: settings.download_cache.clone(),
+    };
 
-    let start_dates = StartDates::load();
+    let start_dates = StartDates::load(settings.market, settings.data_type);
 
     let mut processes_vec: Vec<ProcessData> = vec![];
-    for asset in &settings.assets {
-        let start = start_dates.get_start_date(&asset);
-        let process_data = ProcessData::new(&settings.granularity, &asset, start);
+    for symbol in &settings.symbols {
+        let start = start_dates.get_start_date(symbol);
+        let delisted = settings.delisted_symbols.contains(symbol);
+        let process_data = ProcessData::new(settings.market, settings.data_type, &settings.granularity, settings.schema, symbol, start, delisted);
         processes_vec.push(process_data);
     }
     let master_bar = Arc::new(Mutex::new(multi_progress.add(ProgressBar::new(processes_vec.len() as u64))));
@@ -54,54 +73,99 @@ async fn handle_processes(settings: Settings) {
         .progress_chars("█░"));
 
 
-    let agent = Agent::new();
-    let (tx, rx) = mpsc::channel::<(String, Result<ProcessResult, ScrapperError>)>();
-
-    let mut handles = vec![];
+    let client = settings.client.clone();
+    let max_retries = client.get_retry_policy().get_max_retries();
+    let mut results: Vec<(String, Result<ProcessResult, ScrapperError>)> = vec![];
+    let mut requeued_assets: HashMap<String, u32> = HashMap::new();
 
-    for process in processes_vec {
-        let process_clone = process.clone();
-        let master_bar_clone = Arc::clone(&master_bar);
-        let multi_progress_clone = multi_progress.clone();
-        let agent_clone = agent.clone();
-        let tx_clone = tx.clone();
-        let semaphore_clone = Arc::clone(&semaphore);
-
-        let handle = task::spawn(async move {
-            let _permit = semaphore_clone.acquire().await.unwrap();
-            new_process(process_clone, agent_clone, master_bar_clone, multi_progress_clone, tx_clone).await;
-        });
+    let mut queue = processes_vec;
+    let mut round = 0;
+    while !queue.is_empty() {
+        if round > 0 {
+            tokio::time::sleep(get_backoff_delay(round - 1)).await;
+        }
+        let (tx, rx) = mpsc::channel::<(String, Result<ProcessResult, ScrapperError>)>();
+        let mut handles = vec![];
+
+        for process in &queue {
+            let process_clone = process.clone();
+            let master_bar_clone = Arc::clone(&master_bar);
+            let multi_progress_clone = multi_progress.clone();
+            let client_clone = client.clone();
+            let tx_clone = tx.clone();
+            let pipeline_clone = pipeline.clone();
+
+            let handle = task::spawn(async move {
+                new_process(process_clone, client_clone, pipeline_clone, master_bar_clone, multi_progress_clone, tx_clone).await;
+            });
+
+            handles.push(handle);
+        }
+        drop(tx);
+        for handle in handles {
+            handle.await.unwrap();
+        }
 
-        handles.push(handle);
+        let mut next_queue: Vec<ProcessData> = vec![];
+        while let Ok((symbol, res)) = rx.recv() {
+            let retries = requeued_assets.get(&symbol).copied().unwrap_or(0);
+            match res {
+                //Missing archives and local disk errors won't go away on a new attempt, any other failure is given another chance
+                Err(err) if (!matches!(err, ScrapperError::NoOnlineData | ScrapperError::IOError(_)) || err.is_transient()) && retries < max_retries => {
+                    println!("Asset {} failed with error: {}, it will be retried", symbol, err);
+                    requeued_assets.insert(symbol.clone(), retries + 1);
+                    next_queue.extend(queue.iter().find(|process| process.get_symbol() == symbol).cloned());
+                    master_bar.lock().unwrap().inc_length(1);
+                }
+                res => results.push((symbol, res)),
+            }
+        }
+        queue = next_queue;
+        round += 1;
     }
-    drop(tx);
-    for handle in handles {
-        handle.await.unwrap();
+    post_process(results, settings);
+
+    println!("Retried {} requests and {} assets", client.get_retry_policy().get_retries(), requeued_assets.values().sum::<u32>());
+    for (symbol, retries) in &requeued_assets {
+        println!("Asset {} was retried {} time(s)", symbol, retries);
     }
-    post_process(rx, settings);
 }
 
-async fn new_process(mut process_data: ProcessData, agent: Agent, master_bar: Arc<Mutex<ProgressBar>>, multi_progress: MultiProgress, tx: Sender<(String, Result<ProcessResult, ScrapperError>)>) {
+async fn new_process(mut process_data: ProcessData, client: DownloadClient, pipeline: Pipeline, master_bar: Arc<Mutex<ProgressBar>>, multi_progress: MultiProgress, tx: Sender<(String, Result<ProcessResult, ScrapperError>)>) {
+    //Archives of an asset can't be evicted between their download and their extraction
+    let download_directory = process_data.get_asset_file(process_data.get_end()).get_download_directory();
+    pipeline.download_cache.lock_directory(&download_directory);
+    let download_permit = pipeline.download_semaphore.acquire().await.unwrap();
     process_data.init_progress_bar(&multi_progress);
-    let res = process(&mut process_data, agent);
+    let res = download(&mut process_data, &client).await;
+    drop(download_permit);
+
+    let (mut process_data, res) = match res {
+        Ok(()) => {
+            let _extract_permit = pipeline.extract_semaphore.acquire().await.unwrap();
+            extract(process_data, pipeline.download_cache.clone()).await
+        }
+        Err(err) => (process_data, Err(err)),
+    };
+    pipeline.download_cache.release_directory(&download_directory);
     process_data.finish_progress_bar(&multi_progress);
     master_bar.lock().unwrap().inc(1);
-    tx.send((process_data.get_asset(), res)).unwrap();
+    tx.send((process_data.get_symbol(), res)).unwrap();
 }
 
-fn post_process(rx: Receiver<(String, Result<ProcessResult, ScrapperError>)>, settings: Settings) {
-    let mut manifest = Manifest::new(&settings.granularity.clone());
-    let mut start_dates = StartDates::load();
+fn post_process(results: Vec<(String, Result<ProcessResult, ScrapperError>)>, settings: Settings) {
+    let mut manifest = Manifest::new(settings.market, settings.data_type, &settings.granularity, settings.schema);
+    let mut start_dates = StartDates::load(settings.market, settings.data_type);
 
-    while let Ok(result) = rx.recv() {
+    for result in results {
         match result.1 {
             Err(err) => {
-                //TODO: automatic retry on fail, if it's not a "No data error"
                 println!("Asset {} failed with error: {}", result.0, err);
                 continue;
             }
             Ok(res) => {
                 manifest.add_asset(&result.0, res.time_period);
+                manifest.add_missing_months(&result.0, res.missing_months);
                 if let Some(start_date) = res.start_date {
                     start_dates.set_start_date(&result.0, start_date);
                 }
@@ -115,15 +179,26 @@ fn post_process(rx: Receiver<(String, Result<ProcessResult, ScrapperError>)>, se
     manifest.save().unwrap();
 }
 
-//(Vec<TimePeriod>, TimePeriod)
-fn process(process: &mut ProcessData, agent: Agent) -> Result<ProcessResult, ScrapperError> {
-    let result = (|| {
-        if let Some(start_time) = download_asset(process, agent)? {
-            let extraction_results = extract_asset(process, start_time)?;
-            return Ok(extraction_results);
-        }
-        Err(ScrapperError::NoOnlineData)
-    })();
-    let extracted_result = result?;
-    Ok(ProcessResult { down_times: extracted_result.0, time_period: extracted_result.1, start_date: process.get_start() })
-}
\ No newline at end of file
+async fn download(process: &mut ProcessData, client: &DownloadClient) -> Result<(), ScrapperError> {
+    download_asset(process, client).await?;
+    if process.get_monthly_files().is_empty() && process.get_daily_files().is_empty() {
+        return Err(ScrapperError::NoOnlineData);
+    }
+    Ok(())
+}
+
+//Extraction is CPU bound, it runs on the blocking pool and hands the process back once done
+async fn extract(mut process: ProcessData, download_cache: DownloadCache) -> (ProcessData, Result<ProcessResult, ScrapperError>) {
+    task::spawn_blocking(move || {
+        let result = extract_asset(&mut process).and_then(|extracted_result| {
+            download_cache.apply(&get_asset_files(&process))?;
+            Ok(ProcessResult {
+                down_times: extracted_result.0,
+                time_period: extracted_result.1,
+                start_date: process.get_start(),
+                missing_months: process.get_missing_months(),
+            })
+        });
+        (process, result)
+    }).await.unwrap()
+}
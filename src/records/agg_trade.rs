@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use crate::records::{parse_bool, Record};
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AggTrade {
+    agg_trade_id: u64,
+    price: f64,
+    quantity: f64,
+    first_trade_id: u64,
+    last_trade_id: u64,
+    timestamp: u64,
+    is_buyer_maker: bool,
+}
+
+impl Record for AggTrade {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["agg_trade_id"],
+        &["price"],
+        &["quantity"],
+        &["first_trade_id"],
+        &["last_trade_id"],
+        &["transact_time"],
+        &["is_buyer_maker"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<AggTrade, ScrapperError> {
+        //Spot archives carry an extra "best price match" column that we don't keep
+        let agg_trade_id = values[0].parse::<u64>()?;
+        let price = values[1].parse::<f64>()?;
+        let quantity = values[2].parse::<f64>()?;
+        let first_trade_id = values[3].parse::<u64>()?;
+        let last_trade_id = values[4].parse::<u64>()?;
+        let timestamp = values[5].parse::<u64>()?;
+        let is_buyer_maker = parse_bool(values[6])?;
+        Ok(AggTrade { agg_trade_id, price, quantity, first_trade_id, last_trade_id, timestamp, is_buyer_maker })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    fn to_milliseconds(&mut self) {
+        self.timestamp /= 1_000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::columns::get_headerless_layout;
+    use crate::records::tests::parse_row;
+    use crate::utils::data_type::DataType;
+    use crate::utils::market::Market;
+    use super::*;
+
+    #[test]
+    fn test_spot_row() {
+        //Capitalized booleans and the trailing best match column
+        let layout = get_headerless_layout(DataType::AggTrades, Market::Spot).unwrap();
+        let row = "3391227455,42283.58000000,0.00048000,3339102211,3339102211,1704067200020,True,True";
+        let expected = AggTrade { agg_trade_id: 3391227455, price: 42283.58, quantity: 0.00048, first_trade_id: 3339102211, last_trade_id: 3339102211, timestamp: 1704067200020, is_buyer_maker: true };
+        assert_eq!(parse_row::<AggTrade>(layout, row), expected);
+    }
+
+    #[test]
+    fn test_futures_row() {
+        let header: Vec<&str> = "agg_trade_id,price,quantity,first_trade_id,last_trade_id,transact_time,is_buyer_maker".split(',').collect();
+        let row = "1950356281,42314.0,0.002,4447455497,4447455497,1704067200066,false";
+        let expected = AggTrade { agg_trade_id: 1950356281, price: 42314.0, quantity: 0.002, first_trade_id: 4447455497, last_trade_id: 4447455497, timestamp: 1704067200066, is_buyer_maker: false };
+        assert_eq!(parse_row::<AggTrade>(&header, row), expected);
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use crate::records::{parse_datetime, Record};
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BookDepth {
+    timestamp: u64,
+    percentage: f64,
+    depth: f64,
+    notional: f64,
+}
+
+impl Record for BookDepth {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["timestamp"],
+        &["percentage"],
+        &["depth"],
+        &["notional"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<BookDepth, ScrapperError> {
+        //One row per distance from the mid price, sharing the same timestamp
+        let timestamp = parse_datetime(values[0])?;
+        let percentage = values[1].parse::<f64>()?;
+        let depth = values[2].parse::<f64>()?;
+        let notional = values[3].parse::<f64>()?;
+        Ok(BookDepth { timestamp, percentage, depth, notional })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::tests::parse_row;
+    use super::*;
+
+    #[test]
+    fn test_futures_row() {
+        let header = ["timestamp", "percentage", "depth", "notional"];
+        let expected = BookDepth { timestamp: 1704067208000, percentage: -5.0, depth: 2834.155, notional: 116768469.5227 };
+        assert_eq!(parse_row::<BookDepth>(&header, "2024-01-01 00:00:08,-5,2834.15500000,116768469.52270000"), expected);
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use crate::records::Record;
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BookTicker {
+    update_id: u64,
+    best_bid_price: f64,
+    best_bid_quantity: f64,
+    best_ask_price: f64,
+    best_ask_quantity: f64,
+    transaction_time: u64,
+    event_time: u64,
+}
+
+impl Record for BookTicker {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["update_id"],
+        &["best_bid_price"],
+        &["best_bid_qty"],
+        &["best_ask_price"],
+        &["best_ask_qty"],
+        &["transaction_time"],
+        &["event_time"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<BookTicker, ScrapperError> {
+        let update_id = values[0].parse::<u64>()?;
+        let best_bid_price = values[1].parse::<f64>()?;
+        let best_bid_quantity = values[2].parse::<f64>()?;
+        let best_ask_price = values[3].parse::<f64>()?;
+        let best_ask_quantity = values[4].parse::<f64>()?;
+        let transaction_time = values[5].parse::<u64>()?;
+        let event_time = values[6].parse::<u64>()?;
+        Ok(BookTicker { update_id, best_bid_price, best_bid_quantity, best_ask_price, best_ask_quantity, transaction_time, event_time })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.transaction_time
+    }
+    fn to_milliseconds(&mut self) {
+        self.transaction_time /= 1_000;
+        self.event_time /= 1_000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::tests::parse_row;
+    use super::*;
+
+    #[test]
+    fn test_futures_row() {
+        let header: Vec<&str> = "update_id,best_bid_price,best_bid_qty,best_ask_price,best_ask_qty,transaction_time,event_time".split(',').collect();
+        let row = "3793398286441,42314.0,2.375,42314.1,8.624,1704067200009,1704067200014";
+        let expected = BookTicker { update_id: 3793398286441, best_bid_price: 42314.0, best_bid_quantity: 2.375, best_ask_price: 42314.1, best_ask_quantity: 8.624, transaction_time: 1704067200009, event_time: 1704067200014 };
+        assert_eq!(parse_row::<BookTicker>(&header, row), expected);
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use crate::records::Record;
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BvolIndex {
+    calc_time: u64,
+    index_value: f64,
+}
+
+impl Record for BvolIndex {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["calc_time"],
+        &["index_value"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<BvolIndex, ScrapperError> {
+        //Symbol, base and quote asset columns are already known from the archive
+        let calc_time = values[0].parse::<u64>()?;
+        let index_value = values[1].parse::<f64>()?;
+        Ok(BvolIndex { calc_time, index_value })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.calc_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::tests::parse_row;
+    use super::*;
+
+    #[test]
+    fn test_option_row() {
+        let header = ["calc_time", "symbol", "base_asset", "quote_asset", "index_value"];
+        let expected = BvolIndex { calc_time: 1704412800000, index_value: 52.47 };
+        assert_eq!(parse_row::<BvolIndex>(&header, "1704412800000,BTCBVOLUSDT,BTC,USDT,52.47"), expected);
+    }
+}
@@ -0,0 +1,83 @@
+use csv::StringRecord;
+use crate::utils::data_type::DataType;
+use crate::utils::market::Market;
+
+//Archives without a header row, columns in the order they are published
+pub fn get_headerless_layout(data_type: DataType, market: Market) -> Option<&'static [&'static str]> {
+    match (data_type, market) {
+        (DataType::Klines | DataType::MarkPriceKlines | DataType::IndexPriceKlines | DataType::PremiumIndexKlines, _) => Some(&[
+            "open_time", "open", "high", "low", "close", "volume", "close_time", "quote_volume", "count",
+            "taker_buy_volume", "taker_buy_quote_volume", "ignore"]),
+        (DataType::AggTrades, Market::Spot) => Some(&[
+            "agg_trade_id", "price", "quantity", "first_trade_id", "last_trade_id", "transact_time", "is_buyer_maker",
+            "is_best_match"]),
+        (DataType::AggTrades, _) => Some(&[
+            "agg_trade_id", "price", "quantity", "first_trade_id", "last_trade_id", "transact_time", "is_buyer_maker"]),
+        (DataType::Trades, Market::Spot) => Some(&["id", "price", "qty", "quote_qty", "time", "is_buyer_maker", "is_best_match"]),
+        (DataType::Trades, _) => Some(&["id", "price", "qty", "quote_qty", "time", "is_buyer_maker"]),
+        (DataType::FundingRate, _) => Some(&["calc_time", "funding_interval_hours", "last_funding_rate"]),
+        (DataType::BookTicker, _) => Some(&[
+            "update_id", "best_bid_price", "best_bid_qty", "best_ask_price", "best_ask_qty", "transaction_time",
+            "event_time"]),
+        _ => None,
+    }
+}
+
+//Position in the archive of every column a record needs, in the order of its COLUMNS
+pub struct ColumnMap {
+    indexes: Vec<usize>,
+}
+
+impl ColumnMap {
+    //Fails with the name of the first column that can't be found
+    pub fn new(names: &[&str], columns: &[&[&str]]) -> Result<ColumnMap, String> {
+        let mut indexes: Vec<usize> = vec![];
+        for aliases in columns {
+            let index = names.iter()
+                .position(|name| aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name.trim())))
+                .ok_or(aliases[0].to_string())?;
+            indexes.push(index);
+        }
+        Ok(ColumnMap { indexes })
+    }
+
+    //A header row names at least one of the expected columns, a data row only holds values
+    pub fn is_header(record: &StringRecord, columns: &[&[&str]]) -> bool {
+        record.iter().any(|name| columns.iter().flat_map(|aliases| aliases.iter()).any(|alias| alias.eq_ignore_ascii_case(name.trim())))
+    }
+
+    pub fn select<'a>(&self, record: &'a StringRecord) -> Option<Vec<&'a str>> {
+        self.indexes.iter().map(|&index| record.get(index)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: &[&[&str]] = &[&["open_time"], &["quote_asset_volume", "quote_volume"]];
+
+    #[test]
+    fn test_header_detection() {
+        assert!(ColumnMap::is_header(&StringRecord::from(vec!["open_time", "open", "quote_volume"]), COLUMNS));
+        assert!(!ColumnMap::is_header(&StringRecord::from(vec!["1704067200000", "42283.58", "12.5"]), COLUMNS));
+    }
+
+    #[test]
+    fn test_aliases_and_order() {
+        let map = ColumnMap::new(&["Quote_Volume", "open", "open_time"], COLUMNS).unwrap();
+        let record = StringRecord::from(vec!["12.5", "42283.58", "1704067200000"]);
+        assert_eq!(map.select(&record), Some(vec!["1704067200000", "12.5"]));
+    }
+
+    #[test]
+    fn test_missing_column() {
+        assert_eq!(ColumnMap::new(&["open_time", "open"], COLUMNS).err(), Some("quote_asset_volume".to_string()));
+    }
+
+    #[test]
+    fn test_short_row() {
+        let map = ColumnMap::new(&["open_time", "open", "quote_volume"], COLUMNS).unwrap();
+        assert_eq!(map.select(&StringRecord::from(vec!["1704067200000", "42283.58"])), None);
+    }
+}
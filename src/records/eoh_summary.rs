@@ -0,0 +1,122 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use crate::records::{parse_optional_f64, Record};
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct EohSummary {
+    timestamp: u64,
+    symbol: String,
+    option_type: String,
+    strike: f64,
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+    close: Option<f64>,
+    volume_contracts: Option<f64>,
+    volume_usdt: Option<f64>,
+    best_bid_price: Option<f64>,
+    best_ask_price: Option<f64>,
+    best_bid_quantity: Option<f64>,
+    best_ask_quantity: Option<f64>,
+    best_buy_iv: Option<f64>,
+    best_sell_iv: Option<f64>,
+    mark_price: Option<f64>,
+    mark_iv: Option<f64>,
+    delta: Option<f64>,
+    gamma: Option<f64>,
+    vega: Option<f64>,
+    theta: Option<f64>,
+    open_interest_contracts: Option<f64>,
+    open_interest_usdt: Option<f64>,
+}
+
+impl Record for EohSummary {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["date"],
+        &["hour"],
+        &["symbol"],
+        &["type"],
+        &["strike"],
+        &["open"],
+        &["high"],
+        &["low"],
+        &["close"],
+        &["volume_contracts"],
+        &["volume_usdt"],
+        &["best_bid_price"],
+        &["best_ask_price"],
+        &["best_bid_qty"],
+        &["best_ask_qty"],
+        &["best_buy_iv"],
+        &["best_sell_iv"],
+        &["mark_price"],
+        &["mark_iv"],
+        &["delta"],
+        &["gamma"],
+        &["vega"],
+        &["theta"],
+        &["openinterest_contracts"],
+        &["openinterest_usdt"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<EohSummary, ScrapperError> {
+        //Rows are identified by their date and hour, the underlying column is already known from the archive
+        let date = NaiveDate::parse_from_str(values[0], "%Y-%m-%d")
+            .map_err(|err| ScrapperError::ParseError(format!("Invalid date {}: {}", values[0], err)))?;
+        let hour = values[1].parse::<u32>()?;
+        let timestamp = date.and_hms_opt(hour, 0, 0)
+            .ok_or(ScrapperError::ParseError(format!("Invalid hour {}", hour)))?
+            .and_utc().timestamp_millis() as u64;
+        Ok(EohSummary {
+            timestamp,
+            symbol: values[2].to_string(),
+            option_type: values[3].to_string(),
+            strike: values[4].parse::<f64>()?,
+            open: parse_optional_f64(values[5])?,
+            high: parse_optional_f64(values[6])?,
+            low: parse_optional_f64(values[7])?,
+            close: parse_optional_f64(values[8])?,
+            volume_contracts: parse_optional_f64(values[9])?,
+            volume_usdt: parse_optional_f64(values[10])?,
+            best_bid_price: parse_optional_f64(values[11])?,
+            best_ask_price: parse_optional_f64(values[12])?,
+            best_bid_quantity: parse_optional_f64(values[13])?,
+            best_ask_quantity: parse_optional_f64(values[14])?,
+            best_buy_iv: parse_optional_f64(values[15])?,
+            best_sell_iv: parse_optional_f64(values[16])?,
+            mark_price: parse_optional_f64(values[17])?,
+            mark_iv: parse_optional_f64(values[18])?,
+            delta: parse_optional_f64(values[19])?,
+            gamma: parse_optional_f64(values[20])?,
+            vega: parse_optional_f64(values[21])?,
+            theta: parse_optional_f64(values[22])?,
+            open_interest_contracts: parse_optional_f64(values[23])?,
+            open_interest_usdt: parse_optional_f64(values[24])?,
+        })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::tests::parse_row;
+    use super::*;
+
+    #[test]
+    fn test_option_row() {
+        //Contracts without trades have no open, high, low and close
+        let header: Vec<&str> = "date,hour,symbol,underlying,type,strike,open,high,low,close,volume_contracts,volume_usdt,best_bid_price,best_ask_price,best_bid_qty,best_ask_qty,best_buy_iv,best_sell_iv,mark_price,mark_iv,delta,gamma,vega,theta,openinterest_contracts,openinterest_usdt".split(',').collect();
+        let row = "2023-10-19,1,BTC-231020-27000-C,BTCUSDT,C,27000,,,,,0,0,1.5,2.1,0.35,1.42,0.4,0.62,1.8,0.41,0.00032,0.000001,3.2,-40.1,12.5,358000.2";
+        let record = parse_row::<EohSummary>(&header, row);
+        assert_eq!(record.timestamp, 1697677200000);
+        assert_eq!(record.option_type, "C");
+        assert_eq!(record.strike, 27000.0);
+        assert_eq!(record.open, None);
+        assert_eq!(record.best_bid_price, Some(1.5));
+        assert_eq!(record.theta, Some(-40.1));
+        assert_eq!(record.open_interest_usdt, Some(358000.2));
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use crate::records::Record;
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FullKline {
+    open_time: u64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    close_time: u64,
+    quote_asset_volume: f64,
+    number_of_trades: u64,
+    taker_buy_base_asset_volume: f64,
+    taker_buy_quote_asset_volume: f64,
+}
+
+impl Record for FullKline {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["open_time"],
+        &["open"],
+        &["high"],
+        &["low"],
+        &["close"],
+        &["volume"],
+        &["close_time"],
+        &["quote_asset_volume", "quote_volume"],
+        &["number_of_trades", "count"],
+        &["taker_buy_base_asset_volume", "taker_buy_volume"],
+        &["taker_buy_quote_asset_volume", "taker_buy_quote_volume"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<FullKline, ScrapperError> {
+        //Format based on https://github.com/binance/binance-public-data/, the last column is unused
+        Ok(FullKline {
+            open_time: values[0].parse::<u64>()?,
+            open: values[1].parse::<f64>()?,
+            high: values[2].parse::<f64>()?,
+            low: values[3].parse::<f64>()?,
+            close: values[4].parse::<f64>()?,
+            volume: values[5].parse::<f64>()?,
+            close_time: values[6].parse::<u64>()?,
+            quote_asset_volume: values[7].parse::<f64>()?,
+            number_of_trades: values[8].parse::<u64>()?,
+            taker_buy_base_asset_volume: values[9].parse::<f64>()?,
+            taker_buy_quote_asset_volume: values[10].parse::<f64>()?,
+        })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.open_time
+    }
+    fn to_milliseconds(&mut self) {
+        self.open_time /= 1_000;
+        self.close_time /= 1_000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::tests::parse_row;
+    use super::*;
+
+    #[test]
+    fn test_futures_row() {
+        let header: Vec<&str> = "open_time,open,high,low,close,volume,close_time,quote_volume,count,taker_buy_volume,taker_buy_quote_volume,ignore".split(',').collect();
+        let row = "1704067200000,42314.00,42357.50,42290.00,42335.90,1326.391,1704067259999,56144455.85530,8934,745.684,31565393.04130,0";
+        let expected = FullKline {
+            open_time: 1704067200000,
+            open: 42314.0,
+            high: 42357.5,
+            low: 42290.0,
+            close: 42335.9,
+            volume: 1326.391,
+            close_time: 1704067259999,
+            quote_asset_volume: 56144455.8553,
+            number_of_trades: 8934,
+            taker_buy_base_asset_volume: 745.684,
+            taker_buy_quote_asset_volume: 31565393.0413,
+        };
+        assert_eq!(parse_row::<FullKline>(&header, row), expected);
+    }
+}
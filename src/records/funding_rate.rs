@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use crate::records::Record;
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FundingRate {
+    calc_time: u64,
+    funding_interval_hours: u32,
+    last_funding_rate: f64,
+}
+
+impl Record for FundingRate {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["calc_time"],
+        &["funding_interval_hours"],
+        &["last_funding_rate"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<FundingRate, ScrapperError> {
+        let calc_time = values[0].parse::<u64>()?;
+        let funding_interval_hours = values[1].parse::<u32>()?;
+        let last_funding_rate = values[2].parse::<f64>()?;
+        Ok(FundingRate { calc_time, funding_interval_hours, last_funding_rate })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.calc_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::tests::parse_row;
+    use super::*;
+
+    #[test]
+    fn test_futures_row() {
+        let header = ["calc_time", "funding_interval_hours", "last_funding_rate"];
+        let expected = FundingRate { calc_time: 1704067200000, funding_interval_hours: 8, last_funding_rate: 0.00037409 };
+        assert_eq!(parse_row::<FundingRate>(&header, "1704067200000,8,0.00037409"), expected);
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use crate::records::Record;
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExtractedData {
+    open_time: u64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl PartialEq for ExtractedData {
+    fn eq(&self, other: &Self) -> bool {
+        self.open_time == other.open_time &&
+            self.open == other.open &&
+            self.high == other.high &&
+            self.low == other.low &&
+            self.close == other.close &&
+            self.volume == other.volume
+    }
+}
+
+impl Record for ExtractedData {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["open_time"],
+        &["open"],
+        &["high"],
+        &["low"],
+        &["close"],
+        &["volume"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<ExtractedData, ScrapperError> {
+        //Format based on https://github.com/binance/binance-public-data/
+        let open_time = values[0].parse::<u64>()?;
+        let open = values[1].parse::<f64>()?;
+        let high = values[2].parse::<f64>()?;
+        let low = values[3].parse::<f64>()?;
+        let close = values[4].parse::<f64>()?;
+        let volume = values[5].parse::<f64>()?;
+        Ok(ExtractedData { open_time, open, high, low, close, volume })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.open_time
+    }
+    fn to_milliseconds(&mut self) {
+        self.open_time /= 1_000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::columns::get_headerless_layout;
+    use crate::records::tests::parse_row;
+    use crate::utils::data_type::DataType;
+    use crate::utils::market::Market;
+    use super::*;
+
+    #[test]
+    fn test_spot_row() {
+        let layout = get_headerless_layout(DataType::Klines, Market::Spot).unwrap();
+        let row = "1704067200000,42283.58000000,42554.57000000,42261.02000000,42475.23000000,1271.68108000,1704070799999,53957248.97378900,47134,682.57581000,28957416.81964650,0";
+        let expected = ExtractedData { open_time: 1704067200000, open: 42283.58, high: 42554.57, low: 42261.02, close: 42475.23, volume: 1271.68108 };
+        assert_eq!(parse_row::<ExtractedData>(layout, row), expected);
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use crate::records::{parse_datetime, Record};
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Metrics {
+    create_time: u64,
+    sum_open_interest: f64,
+    sum_open_interest_value: f64,
+    count_top_trader_long_short_ratio: f64,
+    sum_top_trader_long_short_ratio: f64,
+    count_long_short_ratio: f64,
+    sum_taker_long_short_vol_ratio: f64,
+}
+
+impl Record for Metrics {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["create_time"],
+        &["sum_open_interest"],
+        &["sum_open_interest_value"],
+        &["count_toptrader_long_short_ratio"],
+        &["sum_toptrader_long_short_ratio"],
+        &["count_long_short_ratio"],
+        &["sum_taker_long_short_vol_ratio"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<Metrics, ScrapperError> {
+        //The symbol column is already known from the archive
+        let create_time = parse_datetime(values[0])?;
+        let sum_open_interest = values[1].parse::<f64>()?;
+        let sum_open_interest_value = values[2].parse::<f64>()?;
+        let count_top_trader_long_short_ratio = values[3].parse::<f64>()?;
+        let sum_top_trader_long_short_ratio = values[4].parse::<f64>()?;
+        let count_long_short_ratio = values[5].parse::<f64>()?;
+        let sum_taker_long_short_vol_ratio = values[6].parse::<f64>()?;
+        Ok(Metrics {
+            create_time,
+            sum_open_interest,
+            sum_open_interest_value,
+            count_top_trader_long_short_ratio,
+            sum_top_trader_long_short_ratio,
+            count_long_short_ratio,
+            sum_taker_long_short_vol_ratio,
+        })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.create_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::tests::parse_row;
+    use super::*;
+
+    #[test]
+    fn test_futures_row() {
+        let header: Vec<&str> = "create_time,symbol,sum_open_interest,sum_open_interest_value,count_toptrader_long_short_ratio,sum_toptrader_long_short_ratio,count_long_short_ratio,sum_taker_long_short_vol_ratio".split(',').collect();
+        let row = "2024-01-01 00:05:00,BTCUSDT,79569.73700000,3366926716.93110000,1.47253433,1.26316900,1.53219316,0.96834500";
+        let expected = Metrics {
+            create_time: 1704067500000,
+            sum_open_interest: 79569.737,
+            sum_open_interest_value: 3366926716.9311,
+            count_top_trader_long_short_ratio: 1.47253433,
+            sum_top_trader_long_short_ratio: 1.263169,
+            count_long_short_ratio: 1.53219316,
+            sum_taker_long_short_vol_ratio: 0.968345,
+        };
+        assert_eq!(parse_row::<Metrics>(&header, row), expected);
+    }
+}
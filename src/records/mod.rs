@@ -0,0 +1,82 @@
+pub mod columns;
+pub mod kline;
+pub mod full_kline;
+pub mod agg_trade;
+pub mod trade;
+pub mod funding_rate;
+pub mod metrics;
+pub mod book_ticker;
+pub mod book_depth;
+pub mod eoh_summary;
+pub mod bvol_index;
+
+use chrono::NaiveDateTime;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::utils::errors::ScrapperError;
+
+pub trait Record: Serialize + DeserializeOwned + PartialEq + Sized {
+    //Accepted header names of every needed column, values are given to from_csv in this order
+    const COLUMNS: &'static [&'static [&'static str]];
+
+    fn from_csv(values: &[&str]) -> Result<Self, ScrapperError>;
+    fn get_timestamp(&self) -> u64;
+    //Converts microsecond timestamps, only records from archives that switched unit need it
+    fn to_milliseconds(&mut self) {}
+}
+
+pub fn parse_bool(value: &str) -> Result<bool, ScrapperError> {
+    match value.to_lowercase().as_str() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(ScrapperError::ParseError(format!("Invalid boolean value: {}", value))),
+    }
+}
+
+//Empty values are kept as None, options without trades have no prices
+pub fn parse_optional_f64(value: &str) -> Result<Option<f64>, ScrapperError> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(value.parse::<f64>()?))
+}
+
+//Some futures archives store their time as "2024-01-01 00:05:00" (UTC), converted to a millisecond timestamp
+pub fn parse_datetime(value: &str) -> Result<u64, ScrapperError> {
+    let date_time = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .map_err(|err| ScrapperError::ParseError(format!("Invalid date time {}: {}", value, err)))?;
+    Ok(date_time.and_utc().timestamp_millis() as u64)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use csv::StringRecord;
+    use crate::records::columns::ColumnMap;
+    use super::*;
+
+    //Parses a raw archive row the way extraction does, with the names of its columns
+    pub fn parse_row<T: Record>(names: &[&str], row: &str) -> T {
+        let map = ColumnMap::new(names, T::COLUMNS).unwrap();
+        let record = StringRecord::from(row.split(',').collect::<Vec<&str>>());
+        T::from_csv(&map.select(&record).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_bool() {
+        assert!(parse_bool("True").unwrap());
+        assert!(!parse_bool("false").unwrap());
+        assert!(parse_bool("1").is_err());
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        assert_eq!(parse_datetime("2024-01-01 00:05:00").unwrap(), 1704067500000);
+        assert!(parse_datetime("2024-01-01T00:05:00").is_err());
+    }
+
+    #[test]
+    fn test_parse_optional_f64() {
+        assert_eq!(parse_optional_f64("").unwrap(), None);
+        assert_eq!(parse_optional_f64("0.35").unwrap(), Some(0.35));
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use crate::records::{parse_bool, Record};
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Trade {
+    trade_id: u64,
+    price: f64,
+    quantity: f64,
+    quote_quantity: f64,
+    time: u64,
+    is_buyer_maker: bool,
+}
+
+impl Record for Trade {
+    const COLUMNS: &'static [&'static [&'static str]] = &[
+        &["id"],
+        &["price"],
+        &["qty"],
+        &["quote_qty"],
+        &["time"],
+        &["is_buyer_maker"],
+    ];
+
+    fn from_csv(values: &[&str]) -> Result<Trade, ScrapperError> {
+        //Spot archives carry an extra "best price match" column that we don't keep
+        let trade_id = values[0].parse::<u64>()?;
+        let price = values[1].parse::<f64>()?;
+        let quantity = values[2].parse::<f64>()?;
+        let quote_quantity = values[3].parse::<f64>()?;
+        let time = values[4].parse::<u64>()?;
+        let is_buyer_maker = parse_bool(values[5])?;
+        Ok(Trade { trade_id, price, quantity, quote_quantity, time, is_buyer_maker })
+    }
+    fn get_timestamp(&self) -> u64 {
+        self.time
+    }
+    fn to_milliseconds(&mut self) {
+        self.time /= 1_000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records::columns::get_headerless_layout;
+    use crate::records::tests::parse_row;
+    use crate::utils::data_type::DataType;
+    use crate::utils::market::Market;
+    use super::*;
+
+    #[test]
+    fn test_spot_row() {
+        //Capitalized booleans and the trailing best match column
+        let layout = get_headerless_layout(DataType::Trades, Market::Spot).unwrap();
+        let row = "3339102211,42283.58000000,0.00048000,20.29611840,1704067200020,False,True";
+        let expected = Trade { trade_id: 3339102211, price: 42283.58, quantity: 0.00048, quote_quantity: 20.2961184, time: 1704067200020, is_buyer_maker: false };
+        assert_eq!(parse_row::<Trade>(layout, row), expected);
+    }
+}
@@ -1,63 +1,169 @@
+use chrono::{Datelike, NaiveDate};
 use crate::input::GRANULARITIES;
+use crate::utils::data_type::DataType;
+use crate::utils::kline_schema::KlineSchema;
+use crate::utils::market::Market;
 use crate::utils::month_year::MonthYear;
 
-pub const STABLE_COIN: &str = "USDT";
+pub const DEFAULT_QUOTE: &str = "USDT";
 const LOCAL_PATH: &str = "./binance_data/";
 const DOWNLOADS_PATH: &str = "downloads/";
 const OUTPUT_PATH: &str = "output/";
 
 
 pub struct AssetFile {
-    asset: String,
+    market: Market,
+    data_type: DataType,
+    symbol: String,
     granularity: String,
+    schema: KlineSchema,
     month_year: MonthYear,
-    ts_factor: u64,
+    day: Option<u8>,
+    ts_factor: Option<u64>,
 }
 
 impl AssetFile {
-    pub fn new(asset: &str, granularity: &str, month_year: MonthYear) -> AssetFile {
-        let mut ts_factor: u64 = 0;
-        for grn_pair in GRANULARITIES {
-            if granularity == grn_pair.0 {
-                ts_factor = grn_pair.1;
+    pub fn new(market: Market, data_type: DataType, symbol: &str, granularity: &str, schema: KlineSchema, month_year: MonthYear) -> AssetFile {
+        let mut ts_factor = None;
+        if data_type.has_granularity() {
+            for grn_pair in GRANULARITIES {
+                if granularity == grn_pair.0 {
+                    ts_factor = Some(grn_pair.1);
+                }
+            }
+            if ts_factor.is_none() {
+                //TODO: better error handling
+                panic!("Couldn't define a timestamp factor for your granularity");
             }
-        }
-        if ts_factor == 0 {
-            //TODO: better error handling
-            panic!("Couldn't define a timestamp factor for your granularity");
         }
 
-        AssetFile { asset: asset.to_string(), granularity: granularity.to_string(), month_year, ts_factor }
+        AssetFile { market, data_type, symbol: symbol.to_string(), granularity: granularity.to_string(), schema, month_year, day: None, ts_factor }
+    }
+    pub fn new_daily(market: Market, data_type: DataType, symbol: &str, granularity: &str, schema: KlineSchema, date: NaiveDate) -> AssetFile {
+        let mut asset_file = Self::new(market, data_type, symbol, granularity, schema, MonthYear::new(date.month() as u8, date.year()));
+        asset_file.day = Some(date.day() as u8);
+        asset_file
     }
 
     pub fn get_file_name(&self) -> String {
-        format!("{}{}-{}-{}-{}", self.asset, STABLE_COIN, self.granularity, self.month_year.get_year(), self.month_year.get_month_string())
+        let file_name = format!("{}-{}-{}-{}", self.symbol, self.get_dataset_name(), self.month_year.get_year(), self.month_year.get_month_string());
+        match self.day {
+            Some(day) => format!("{}-{:02}", file_name, day),
+            None => file_name,
+        }
     }
     pub fn get_download_directory(&self) -> String {
         self.get_local_directory(DOWNLOADS_PATH)
     }
+    pub fn get_downloads_directory() -> String {
+        format!("{}{}", LOCAL_PATH, DOWNLOADS_PATH)
+    }
     pub fn get_extract_directory(&self) -> String {
-        format!("{}{}{}/", LOCAL_PATH, OUTPUT_PATH, self.granularity)
+        Self::get_extract_directory_from_values(self.market, &self.get_dataset_name())
+    }
+    //Spot keeps the historical layout so existing outputs remain valid, other markets get their own directory
+    pub fn get_extract_directory_from_values(market: Market, dataset_name: &str) -> String {
+        match market {
+            Market::Spot => format!("{}{}{}/", LOCAL_PATH, OUTPUT_PATH, dataset_name),
+            _ => format!("{}{}{}/{}/", LOCAL_PATH, OUTPUT_PATH, market.get_name(), dataset_name),
+        }
     }
     pub fn get_result_file_path(&self) -> String {
-        Self::get_result_file_path_from_values(&self.granularity, &self.asset)
+        Self::get_result_file_path_from_values(self.market, self.data_type, &self.granularity, self.schema, &self.symbol)
     }
-    pub fn get_result_file_path_from_values(granularity: &str, asset: &str) -> String {
-        format!("{}{}{}/{}{}.bin", LOCAL_PATH, OUTPUT_PATH, granularity, asset, STABLE_COIN)
+    pub fn get_result_file_path_from_values(market: Market, data_type: DataType, granularity: &str, schema: KlineSchema, symbol: &str) -> String {
+        format!("{}{}{}.bin", Self::get_extract_directory_from_values(market, &data_type.get_dataset_name(granularity)), symbol, Self::get_output_suffix_from_values(data_type, schema))
+    }
+    pub fn get_manifest_path_from_values(market: Market, data_type: DataType, granularity: &str, schema: KlineSchema) -> String {
+        format!("{}manifest{}.json", Self::get_extract_directory_from_values(market, &data_type.get_dataset_name(granularity)), Self::get_output_suffix_from_values(data_type, schema))
+    }
+    //Only klines have a schema, data types without granularity ignore it
+    fn get_output_suffix_from_values(data_type: DataType, schema: KlineSchema) -> String {
+        let schema_suffix = if data_type.has_granularity() { schema.get_output_suffix() } else { "" };
+        format!("{}{}", data_type.get_output_suffix(), schema_suffix)
     }
 
     pub fn get_full_file_name(&self, extension: &str) -> String {
         self.get_file_name() + extension
     }
-    pub fn get_download_url(&self, extension: &str) -> String {
-        format!("https://data.binance.vision/data/spot/monthly/klines/{}{}/{}/{}", self.asset, STABLE_COIN, self.granularity, self.get_full_file_name(extension))
+    //Path of the archive below the data root, the same on every mirror
+    pub fn get_archive_path(&self, extension: &str) -> String {
+        let prefix = Self::get_listing_prefix_from_values(self.market, self.data_type, &self.symbol, &self.granularity, self.day.is_some());
+        prefix + &self.get_full_file_name(extension)
+    }
+    //Key prefix shared by every archive of a symbol, both in the bucket listing and in archive paths
+    pub fn get_listing_prefix_from_values(market: Market, data_type: DataType, symbol: &str, granularity: &str, daily: bool) -> String {
+        let period = if daily { "daily" } else { "monthly" };
+        let granularity_path = if data_type.has_granularity() { format!("{}/", granularity) } else { String::new() };
+        format!("data/{}/{}/{}/{}/{}", market.get_url_path(), period, data_type.get_name(), symbol, granularity_path)
     }
 
+    //Spot keeps the historical layout so existing downloads remain valid, other markets mirror the archive layout
+    //since kline variants share the same file names
     fn get_local_directory(&self, directory: &str) -> String {
-        format!("{}{}{}/{}{}/", LOCAL_PATH, directory, self.granularity, self.asset, STABLE_COIN)
+        if self.market == Market::Spot {
+            return format!("{}{}{}/{}/", LOCAL_PATH, directory, self.get_dataset_name(), self.symbol);
+        }
+        let granularity_path = if self.data_type.has_granularity() { format!("{}/", self.granularity) } else { String::new() };
+        format!("{}{}{}/{}/{}/{}", LOCAL_PATH, directory, self.market.get_name(), self.data_type.get_name(), self.symbol, granularity_path)
+    }
+    fn get_dataset_name(&self) -> String {
+        self.data_type.get_dataset_name(&self.granularity)
+    }
+    pub fn get_market(&self) -> Market {
+        self.market
+    }
+    pub fn get_data_type(&self) -> DataType {
+        self.data_type
     }
-    pub fn get_ts_factor(&self) -> u64 {
+    pub fn get_ts_factor(&self) -> Option<u64> {
         self.ts_factor
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_daily_file(market: Market, data_type: DataType, symbol: &str, granularity: &str) -> AssetFile {
+        AssetFile::new_daily(market, data_type, symbol, granularity, KlineSchema::Slim, NaiveDate::from_ymd_opt(2024, 1, 5).unwrap())
+    }
+
+    #[test]
+    fn test_spot_paths() {
+        let asset_file = AssetFile::new(Market::Spot, DataType::Klines, "BTCUSDT", "1m", KlineSchema::Slim, MonthYear::new(1, 2024));
+        assert_eq!(asset_file.get_archive_path(".zip"), "data/spot/monthly/klines/BTCUSDT/1m/BTCUSDT-1m-2024-01.zip");
+        assert_eq!(asset_file.get_download_directory(), "./binance_data/downloads/1m/BTCUSDT/");
+        assert_eq!(asset_file.get_result_file_path(), "./binance_data/output/1m/BTCUSDT.bin");
+    }
+
+    #[test]
+    fn test_futures_paths() {
+        let asset_file = AssetFile::new(Market::UsdM, DataType::FundingRate, "BTCUSDT", "1m", KlineSchema::Slim, MonthYear::new(1, 2024));
+        assert_eq!(asset_file.get_archive_path(".zip"), "data/futures/um/monthly/fundingRate/BTCUSDT/BTCUSDT-fundingRate-2024-01.zip");
+        //Funding rates are only published monthly
+        assert!(!DataType::FundingRate.has_daily_archives());
+
+        let asset_file = get_daily_file(Market::UsdM, DataType::MarkPriceKlines, "BTCUSDT", "1h");
+        assert_eq!(asset_file.get_archive_path(".zip.CHECKSUM"), "data/futures/um/daily/markPriceKlines/BTCUSDT/1h/BTCUSDT-1h-2024-01-05.zip.CHECKSUM");
+        assert_eq!(asset_file.get_download_directory(), "./binance_data/downloads/um/markPriceKlines/BTCUSDT/1h/");
+        assert_eq!(asset_file.get_result_file_path(), "./binance_data/output/um/1h/BTCUSDT-markPrice.bin");
+
+        let asset_file = get_daily_file(Market::CoinM, DataType::Klines, "BTCUSD_PERP", "1d");
+        assert_eq!(asset_file.get_archive_path(".zip"), "data/futures/cm/daily/klines/BTCUSD_PERP/1d/BTCUSD_PERP-1d-2024-01-05.zip");
+    }
+
+    #[test]
+    fn test_option_paths() {
+        let asset_file = get_daily_file(Market::Options, DataType::BvolIndex, "BTCBVOLUSDT", "1m");
+        assert_eq!(asset_file.get_archive_path(".zip"), "data/option/daily/BVOLIndex/BTCBVOLUSDT/BTCBVOLUSDT-BVOLIndex-2024-01-05.zip");
+        assert_eq!(asset_file.get_result_file_path(), "./binance_data/output/option/BVOLIndex/BTCBVOLUSDT.bin");
+    }
+
+    #[test]
+    fn test_full_schema_paths() {
+        let asset_file = AssetFile::new(Market::UsdM, DataType::IndexPriceKlines, "BTCUSDT", "1m", KlineSchema::Full, MonthYear::new(1, 2024));
+        assert_eq!(asset_file.get_result_file_path(), "./binance_data/output/um/1m/BTCUSDT-indexPrice-full.bin");
+        assert_eq!(AssetFile::get_manifest_path_from_values(Market::Spot, DataType::Klines, "1m", KlineSchema::Full), "./binance_data/output/1m/manifest-full.json");
+    }
+}
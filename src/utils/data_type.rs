@@ -0,0 +1,92 @@
+use serde::Serialize;
+use crate::utils::market::Market;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataType {
+    Klines,
+    AggTrades,
+    Trades,
+    FundingRate,
+    MarkPriceKlines,
+    IndexPriceKlines,
+    PremiumIndexKlines,
+    Metrics,
+    BookTicker,
+    BookDepth,
+    #[serde(rename = "EOHSummary")]
+    EohSummary,
+    #[serde(rename = "BVOLIndex")]
+    BvolIndex,
+}
+
+pub const DATA_TYPES: [(&str, DataType); 12] = [
+    ("klines", DataType::Klines),
+    ("aggTrades", DataType::AggTrades),
+    ("trades", DataType::Trades),
+    ("fundingRate", DataType::FundingRate),
+    ("markPriceKlines", DataType::MarkPriceKlines),
+    ("indexPriceKlines", DataType::IndexPriceKlines),
+    ("premiumIndexKlines", DataType::PremiumIndexKlines),
+    ("metrics", DataType::Metrics),
+    ("bookTicker", DataType::BookTicker),
+    ("bookDepth", DataType::BookDepth),
+    ("EOHSummary", DataType::EohSummary),
+    ("BVOLIndex", DataType::BvolIndex)];
+
+impl DataType {
+    pub fn from_name(name: &str) -> Option<DataType> {
+        DATA_TYPES.iter().find(|&&(key, _)| key == name).map(|&(_, data_type)| data_type)
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            DataType::Klines => "klines",
+            DataType::AggTrades => "aggTrades",
+            DataType::Trades => "trades",
+            DataType::FundingRate => "fundingRate",
+            DataType::MarkPriceKlines => "markPriceKlines",
+            DataType::IndexPriceKlines => "indexPriceKlines",
+            DataType::PremiumIndexKlines => "premiumIndexKlines",
+            DataType::Metrics => "metrics",
+            DataType::BookTicker => "bookTicker",
+            DataType::BookDepth => "bookDepth",
+            DataType::EohSummary => "EOHSummary",
+            DataType::BvolIndex => "BVOLIndex",
+        }
+    }
+    pub fn has_granularity(&self) -> bool {
+        matches!(self, DataType::Klines | DataType::MarkPriceKlines | DataType::IndexPriceKlines | DataType::PremiumIndexKlines)
+    }
+    pub fn is_available_on(&self, market: Market) -> bool {
+        match self {
+            DataType::EohSummary | DataType::BvolIndex => market == Market::Options,
+            _ if market == Market::Options => false,
+            DataType::Klines | DataType::AggTrades | DataType::Trades => true,
+            _ => market != Market::Spot,
+        }
+    }
+    pub fn has_monthly_archives(&self) -> bool {
+        !matches!(self, DataType::Metrics | DataType::BookDepth | DataType::EohSummary | DataType::BvolIndex)
+    }
+    pub fn has_daily_archives(&self) -> bool {
+        !matches!(self, DataType::FundingRate)
+    }
+    //Granularity for klines, data type name otherwise, used in archive names and local directories
+    pub fn get_dataset_name(&self, granularity: &str) -> String {
+        if self.has_granularity() {
+            granularity.to_string()
+        } else {
+            self.get_name().to_string()
+        }
+    }
+    //Kline variants share the trade-price klines directory, their files are told apart by this suffix
+    pub fn get_output_suffix(&self) -> &'static str {
+        match self {
+            DataType::MarkPriceKlines => "-markPrice",
+            DataType::IndexPriceKlines => "-indexPrice",
+            DataType::PremiumIndexKlines => "-premiumIndex",
+            _ => "",
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+use std::fs::{File, FileTimes, read_dir, remove_dir, remove_file};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use crate::utils::asset_file::AssetFile;
+use crate::utils::errors::ScrapperError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CachePolicy {
+    Keep,
+    //Archives of an asset are deleted once its output is written and checked
+    Clear,
+    //Least recently used archives are deleted once the downloads directory exceeds this size, in bytes
+    MaxSize(u64),
+}
+
+struct CachedFile {
+    path: String,
+    size: u64,
+    accessed: SystemTime,
+}
+
+//Shared by every asset, directories of assets being processed are never evicted
+#[derive(Clone)]
+pub struct DownloadCache {
+    policy: CachePolicy,
+    in_use: Arc<Mutex<HashSet<String>>>,
+}
+
+impl DownloadCache {
+    pub fn new(policy: CachePolicy) -> DownloadCache {
+        DownloadCache { policy, in_use: Arc::new(Mutex::new(HashSet::new())) }
+    }
+
+    pub fn lock_directory(&self, directory: &str) {
+        self.in_use.lock().unwrap().insert(directory.to_string());
+    }
+    pub fn release_directory(&self, directory: &str) {
+        self.in_use.lock().unwrap().remove(directory);
+    }
+
+    //Called once the output of the asset is safely written, its archives are no longer needed by this run
+    pub fn apply(&self, asset_files: &[AssetFile]) -> Result<(), ScrapperError> {
+        match self.policy {
+            CachePolicy::Keep => Ok(()),
+            CachePolicy::Clear => {
+                for asset_file in asset_files {
+                    for extension in [".zip", ".zip.CHECKSUM"] {
+                        let path = asset_file.get_download_directory() + &asset_file.get_full_file_name(extension);
+                        if Path::new(&path).exists() {
+                            remove_file(&path)?;
+                        }
+                    }
+                }
+                if let Some(asset_file) = asset_files.first() {
+                    //Fails while the directory still holds archives of another data set, which are kept
+                    let _ = remove_dir(asset_file.get_download_directory());
+                }
+                Ok(())
+            }
+            CachePolicy::MaxSize(max_size) => {
+                for asset_file in asset_files {
+                    for extension in [".zip", ".zip.CHECKSUM"] {
+                        touch(&(asset_file.get_download_directory() + &asset_file.get_full_file_name(extension)));
+                    }
+                }
+                self.evict(max_size)
+            }
+        }
+    }
+
+    fn evict(&self, max_size: u64) -> Result<(), ScrapperError> {
+        //Holding the lock keeps evictions of parallel assets from racing each other
+        let in_use = self.in_use.lock().unwrap();
+        let mut cached_files: Vec<CachedFile> = vec![];
+        list_cached_files(&AssetFile::get_downloads_directory(), &mut cached_files)?;
+
+        let cache_size: u64 = cached_files.iter().map(|cached_file| cached_file.size).sum();
+        let evictable_files = cached_files.into_iter()
+            .filter(|cached_file| !in_use.iter().any(|directory| cached_file.path.starts_with(directory.as_str())))
+            .collect();
+        for path in get_evicted_files(evictable_files, cache_size, max_size) {
+            remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+//Access times are set explicitly, mounts with noatime or relatime don't record reads
+fn touch(path: &str) {
+    if let Ok(file) = File::open(path) {
+        let _ = file.set_times(FileTimes::new().set_accessed(SystemTime::now()));
+    }
+}
+
+fn list_cached_files(directory: &str, cached_files: &mut Vec<CachedFile>) -> Result<(), ScrapperError> {
+    let entries = match read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let path = format!("{}{}", directory, entry.file_name().to_string_lossy());
+        if metadata.is_dir() {
+            list_cached_files(&(path + "/"), cached_files)?;
+        } else {
+            let accessed = metadata.accessed().or(metadata.modified())?;
+            cached_files.push(CachedFile { path, size: metadata.len(), accessed });
+        }
+    }
+    Ok(())
+}
+
+//Oldest accessed files first, until the cache fits in its maximum size
+fn get_evicted_files(mut cached_files: Vec<CachedFile>, mut cache_size: u64, max_size: u64) -> Vec<String> {
+    cached_files.sort_by_key(|cached_file| cached_file.accessed);
+    let mut evicted_files: Vec<String> = vec![];
+    for cached_file in cached_files {
+        if cache_size <= max_size {
+            break;
+        }
+        cache_size -= cached_file.size;
+        evicted_files.push(cached_file.path);
+    }
+    evicted_files
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::*;
+
+    #[test]
+    fn test_evicted_files() {
+        let now = SystemTime::now();
+        let cached_files = vec![
+            CachedFile { path: "recent.zip".to_string(), size: 40, accessed: now },
+            CachedFile { path: "oldest.zip".to_string(), size: 30, accessed: now - Duration::from_secs(20) },
+            CachedFile { path: "old.zip".to_string(), size: 30, accessed: now - Duration::from_secs(10) },
+        ];
+        assert_eq!(get_evicted_files(cached_files, 100, 50), vec!["oldest.zip", "old.zip"]);
+        assert!(get_evicted_files(vec![], 100, 50).is_empty());
+    }
+}
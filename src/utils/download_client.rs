@@ -0,0 +1,164 @@
+use std::fs;
+use std::time::Duration;
+use reqwest::{Certificate, Client, Proxy, RequestBuilder, Response, StatusCode};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use crate::utils::download_pool::DownloadPool;
+use crate::utils::endpoints::Endpoints;
+use crate::utils::errors::ScrapperError;
+use crate::utils::rate_limiter::RateLimiter;
+use crate::utils::retry_policy::RetryPolicy;
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+//Everything needed to reach the servers, shared by every asset so that limits apply to the whole run
+#[derive(Clone)]
+pub struct DownloadClient {
+    client: Client,
+    endpoints: Endpoints,
+    pool: DownloadPool,
+    retry_policy: RetryPolicy,
+    request_limiter: RateLimiter,
+    bandwidth_limiter: RateLimiter,
+}
+
+pub struct DownloadClientBuilder {
+    endpoints: Endpoints,
+    pool: DownloadPool,
+    retry_policy: RetryPolicy,
+    request_limiter: RateLimiter,
+    bandwidth_limiter: RateLimiter,
+    proxy: Option<String>,
+    ca_bundle: Option<String>,
+    connect_timeout: Duration,
+    timeout: Duration,
+    user_agent: String,
+}
+
+impl DownloadClient {
+    pub fn builder(endpoints: Endpoints) -> DownloadClientBuilder {
+        DownloadClientBuilder {
+            endpoints,
+            pool: DownloadPool::new(1, 1),
+            retry_policy: RetryPolicy::new(0),
+            request_limiter: RateLimiter::new(None),
+            bandwidth_limiter: RateLimiter::new(None),
+            proxy: None,
+            ca_bundle: None,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        }
+    }
+    //Waits for the request rate limit before handing out the request
+    pub async fn request(&self, url: &str) -> RequestBuilder {
+        self.request_limiter.acquire(1).await;
+        self.client.get(url)
+    }
+    //Missing files are reported as NoOnlineData, any other error status as a network error
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, ScrapperError> {
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(ScrapperError::NoOnlineData);
+        }
+        Ok(response.error_for_status()?)
+    }
+    pub async fn read_to_string(&self, response: Response) -> Result<String, ScrapperError> {
+        let mut payload: Vec<u8> = vec![];
+        self.copy_body(response, &mut payload).await?;
+        String::from_utf8(payload).map_err(|err| ScrapperError::ParseError(err.to_string()))
+    }
+    //Streams the body chunk by chunk, each chunk waits for the bandwidth limit
+    pub async fn copy_body<W: AsyncWrite + Unpin>(&self, mut response: Response, writer: &mut W) -> Result<(), ScrapperError> {
+        while let Some(chunk) = response.chunk().await? {
+            self.bandwidth_limiter.acquire(chunk.len() as u64).await;
+            writer.write_all(&chunk).await?;
+        }
+        writer.flush().await?;
+        Ok(())
+    }
+    pub fn get_endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+    pub fn get_pool(&self) -> &DownloadPool {
+        &self.pool
+    }
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+}
+
+impl DownloadClientBuilder {
+    pub fn pool(mut self, pool: DownloadPool) -> DownloadClientBuilder {
+        self.pool = pool;
+        self
+    }
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> DownloadClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+    pub fn request_limiter(mut self, request_limiter: RateLimiter) -> DownloadClientBuilder {
+        self.request_limiter = request_limiter;
+        self
+    }
+    pub fn bandwidth_limiter(mut self, bandwidth_limiter: RateLimiter) -> DownloadClientBuilder {
+        self.bandwidth_limiter = bandwidth_limiter;
+        self
+    }
+    //http://, https://, socks5:// or socks5h:// url, credentials can be given as user:password@host
+    pub fn proxy(mut self, proxy: Option<String>) -> DownloadClientBuilder {
+        self.proxy = proxy;
+        self
+    }
+    //PEM file of root certificates trusted on top of the default ones
+    pub fn ca_bundle(mut self, ca_bundle: Option<String>) -> DownloadClientBuilder {
+        self.ca_bundle = ca_bundle;
+        self
+    }
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> DownloadClientBuilder {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+    //Applies to every read on the connection, not to the whole download
+    pub fn timeout(mut self, timeout: Duration) -> DownloadClientBuilder {
+        self.timeout = timeout;
+        self
+    }
+    pub fn user_agent(mut self, user_agent: String) -> DownloadClientBuilder {
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn build(self) -> Result<DownloadClient, String> {
+        //Without an explicit proxy, the usual ALL_PROXY, HTTPS_PROXY and HTTP_PROXY variables are honored
+        let mut client_builder = Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.timeout)
+            .user_agent(&self.user_agent);
+        if let Some(proxy) = &self.proxy {
+            client_builder = client_builder.proxy(Proxy::all(proxy).map_err(|err| format!("Invalid proxy {}: {}", proxy, err))?);
+        }
+        if let Some(ca_bundle) = &self.ca_bundle {
+            for certificate in get_certificates(ca_bundle)? {
+                client_builder = client_builder.add_root_certificate(certificate);
+            }
+        }
+        Ok(DownloadClient {
+            client: client_builder.build().map_err(|err| err.to_string())?,
+            endpoints: self.endpoints,
+            pool: self.pool,
+            retry_policy: self.retry_policy,
+            request_limiter: self.request_limiter,
+            bandwidth_limiter: self.bandwidth_limiter,
+        })
+    }
+}
+
+fn get_certificates(ca_bundle: &str) -> Result<Vec<Certificate>, String> {
+    let content = fs::read(ca_bundle).map_err(|err| format!("Can't read CA bundle {}: {}", ca_bundle, err))?;
+    let certificates = Certificate::from_pem_bundle(&content).map_err(|err| format!("Invalid certificate in {}: {}", ca_bundle, err))?;
+    if certificates.is_empty() {
+        return Err(format!("No certificate found in {}", ca_bundle));
+    }
+    Ok(certificates)
+}
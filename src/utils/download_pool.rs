@@ -0,0 +1,22 @@
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+//Bounds the archive downloads running at once, both within an asset and across every asset
+#[derive(Clone)]
+pub struct DownloadPool {
+    per_asset: usize,
+    available: Arc<Semaphore>,
+}
+
+impl DownloadPool {
+    pub fn new(per_asset: usize, global: usize) -> DownloadPool {
+        DownloadPool { per_asset, available: Arc::new(Semaphore::new(global)) }
+    }
+    pub fn get_per_asset(&self) -> usize {
+        self.per_asset
+    }
+    //Waits until one of the global download slots is free
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        Arc::clone(&self.available).acquire_owned().await.unwrap()
+    }
+}
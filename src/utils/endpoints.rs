@@ -0,0 +1,60 @@
+use std::{env, fs};
+use serde::Deserialize;
+use crate::utils::market::Market;
+
+const DEFAULT_DATA_URL: &str = "https://data.binance.vision";
+//S3 bucket behind data.binance.vision, the public domain doesn't answer listing requests
+const DEFAULT_LISTING_URL: &str = "https://s3-ap-northeast-1.amazonaws.com/data.binance.vision";
+const DATA_URL_ENV: &str = "BINANCE_DATA_URL";
+const API_URL_ENV: &str = "BINANCE_API_URL";
+const CONFIG_PATH: &str = "./scrapper_config.json";
+const LOCAL_SCHEME: &str = "file://";
+
+#[derive(Deserialize, Default)]
+struct EndpointsConfig {
+    data_url: Option<String>,
+    api_url: Option<String>,
+}
+
+//Archives and exchange info roots, a mirror must keep the original paths below its root
+#[derive(Clone, Debug)]
+pub struct Endpoints {
+    data_url: String,
+    api_url: Option<String>,
+}
+
+impl Endpoints {
+    //Command line values come first, then environment variables, then the config file
+    pub fn new(data_url: Option<String>, api_url: Option<String>) -> Endpoints {
+        let config: EndpointsConfig = match fs::read_to_string(CONFIG_PATH) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| panic!("Invalid config file {}: {}", CONFIG_PATH, err)),
+            Err(_) => EndpointsConfig::default(),
+        };
+        let data_url = data_url.or(env::var(DATA_URL_ENV).ok()).or(config.data_url)
+            .unwrap_or(DEFAULT_DATA_URL.to_string());
+        let api_url = api_url.or(env::var(API_URL_ENV).ok()).or(config.api_url);
+        Endpoints {
+            data_url: data_url.trim_end_matches('/').to_string(),
+            api_url: api_url.map(|api_url| api_url.trim_end_matches('/').to_string()),
+        }
+    }
+
+    pub fn get_data_url(&self, path: &str) -> String {
+        format!("{}/{}", self.data_url, path)
+    }
+    //Mirrors are expected to answer listing requests themselves, on failure archives are probed
+    pub fn get_listing_url(&self) -> &str {
+        if self.data_url == DEFAULT_DATA_URL { DEFAULT_LISTING_URL } else { &self.data_url }
+    }
+    pub fn get_exchange_info_url(&self, market: Market) -> String {
+        match &self.api_url {
+            Some(api_url) => format!("{}{}", api_url, market.get_exchange_info_path()),
+            None => format!("{}{}", market.get_api_host(), market.get_exchange_info_path()),
+        }
+    }
+}
+
+//file:// urls point to a local directory tree with the same layout as the servers
+pub fn get_local_path(url: &str) -> Option<&str> {
+    url.strip_prefix(LOCAL_SCHEME)
+}
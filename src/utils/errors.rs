@@ -1,11 +1,13 @@
 use std::{fmt, io};
 use std::num::{ParseFloatError, ParseIntError};
+use reqwest::StatusCode;
 
+#[derive(Debug)]
 pub enum ScrapperError {
     IOError(io::Error),
     ZipError(zip::result::ZipError),
     CsvError(csv::Error),
-    NetworkError(ureq::Error),
+    NetworkError(Box<reqwest::Error>),
     ParseError(String),
     IntegrityError(String),
     NoOnlineData,
@@ -25,6 +27,28 @@ impl fmt::Display for ScrapperError {
     }
 }
 
+impl ScrapperError {
+    //Failures worth retrying, a missing archive or a corrupted file won't change on the next attempt
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ScrapperError::NetworkError(error) => match error.status() {
+                Some(status) => status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+                None => error.is_timeout() || error.is_connect() || error.is_request() || error.is_body(),
+            },
+            //Interrupted bodies surface as IO errors while copying the response, local disk errors won't go away on retry
+            ScrapperError::IOError(error) => matches!(error.kind(),
+                io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted),
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ScrapperError {
+    fn from(error: reqwest::Error) -> Self {
+        ScrapperError::NetworkError(Box::new(error))
+    }
+}
+
 impl From<io::Error> for ScrapperError {
     fn from(error: io::Error) -> Self {
         ScrapperError::IOError(error)
@@ -61,3 +85,16 @@ impl From<bincode::Error> for ScrapperError {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_io_errors() {
+        assert!(ScrapperError::IOError(io::Error::from(io::ErrorKind::UnexpectedEof)).is_transient());
+        assert!(ScrapperError::IOError(io::Error::from(io::ErrorKind::ConnectionReset)).is_transient());
+        assert!(!ScrapperError::IOError(io::Error::from(io::ErrorKind::PermissionDenied)).is_transient());
+        assert!(!ScrapperError::IOError(io::Error::from(io::ErrorKind::StorageFull)).is_transient());
+    }
+}
@@ -3,29 +3,29 @@ use std::io::Read;
 use sha2::{Digest, Sha256};
 use crate::ScrapperError;
 
-pub fn check_zip_integrity(file_path: &str) -> Result<(), ScrapperError> {
-    let checksum_path = format!("{}{}", file_path, ".CHECKSUM");
-    if metadata(file_path).is_err() || metadata(&checksum_path).is_err() {
-        return Err(ScrapperError::IntegrityError("TODOA:".to_string()));
+//Checks a zip against a checksum file stored anywhere, partial downloads are checked before being moved into place
+pub fn check_zip_checksum(file_path: &str, checksum_path: &str) -> Result<(), ScrapperError> {
+    if metadata(file_path).is_err() || metadata(checksum_path).is_err() {
+        return Err(ScrapperError::IntegrityError(format!("Missing {} or its checksum", file_path)));
     }
 
-    let checksum_read = read_to_string(&checksum_path)?;
+    let checksum_read = read_to_string(checksum_path)?;
     let checksum_content: Vec<&str> = checksum_read.split_whitespace().collect();
     let expected_checksum = {
-        if let Some(expected_checksum) = checksum_content.get(0).cloned() {
+        if let Some(expected_checksum) = checksum_content.first().cloned() {
             expected_checksum.to_string()
         } else {
-            return Err(ScrapperError::IntegrityError("TODOB:".to_string()));
+            return Err(ScrapperError::IntegrityError(format!("Empty checksum file {}", checksum_path)));
         }
     };
 
     let actual_checksum = calculate_checksum(file_path)?;
 
-    return if expected_checksum == actual_checksum {
+    if expected_checksum == actual_checksum {
         Ok(())
     } else {
-        Err(ScrapperError::IntegrityError("TODOC:".to_string()))
-    };
+        Err(ScrapperError::IntegrityError(format!("Checksum mismatch for {}", file_path)))
+    }
 }
 
 fn calculate_checksum(file_path: &str) -> Result<String, std::io::Error> {
@@ -0,0 +1,25 @@
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KlineSchema {
+    Slim,
+    Full,
+}
+
+pub const KLINE_SCHEMAS: [(&str, KlineSchema); 2] = [
+    ("slim", KlineSchema::Slim),
+    ("full", KlineSchema::Full)];
+
+impl KlineSchema {
+    pub fn from_name(name: &str) -> Option<KlineSchema> {
+        KLINE_SCHEMAS.iter().find(|&&(key, _)| key == name).map(|&(_, schema)| schema)
+    }
+    //Full records don't share the slim layout, their files are told apart by this suffix
+    pub fn get_output_suffix(&self) -> &'static str {
+        match self {
+            KlineSchema::Slim => "",
+            KlineSchema::Full => "-full",
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::io::ErrorKind;
+use chrono::{DateTime, Utc};
+use tokio::fs::read_dir;
+use crate::utils::download_client::DownloadClient;
+use crate::utils::endpoints::get_local_path;
+use crate::utils::errors::ScrapperError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListedFile {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: DateTime<Utc>,
+}
+
+impl ListedFile {
+    pub fn get_file_name(&self) -> &str {
+        self.key.rsplit('/').next().unwrap_or(&self.key)
+    }
+}
+
+//Lists every file under the prefix, keys after the marker only when one is given
+pub async fn list_files(client: &DownloadClient, prefix: &str, marker: Option<&str>) -> Result<Vec<ListedFile>, ScrapperError> {
+    if let Some(root) = get_local_path(client.get_endpoints().get_listing_url()) {
+        return list_local_files(root, prefix, marker).await;
+    }
+    let mut listed_files: Vec<ListedFile> = vec![];
+    let mut marker = marker.map(|marker| marker.to_string());
+    loop {
+        let payload = client.get_retry_policy().run(|| async {
+            let mut request = client.request(client.get_endpoints().get_listing_url()).await
+                .query(&[("delimiter", "/"), ("prefix", prefix)]);
+            if let Some(marker) = &marker {
+                request = request.query(&[("marker", marker)]);
+            }
+            //A missing listing stays a network error, the archives are then probed one by one
+            let response = request.send().await?.error_for_status()?;
+            client.read_to_string(response).await
+        }).await?;
+        let (page, truncated) = parse_listing(&payload)?;
+        marker = page.last().map(|file| file.key.clone());
+        listed_files.extend(page);
+        if !truncated || marker.is_none() {
+            break;
+        }
+    }
+    Ok(listed_files)
+}
+
+//Keys are sorted like the bucket does, so that markers behave the same on a local tree
+async fn list_local_files(root: &str, prefix: &str, marker: Option<&str>) -> Result<Vec<ListedFile>, ScrapperError> {
+    let mut entries = match read_dir(format!("{}/{}", root, prefix)).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+    let mut listed_files: Vec<ListedFile> = vec![];
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let key = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if marker.is_some_and(|marker| key.as_str() <= marker) {
+            continue;
+        }
+        listed_files.push(ListedFile { key, size: metadata.len(), last_modified: DateTime::from(metadata.modified()?) });
+    }
+    listed_files.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(listed_files)
+}
+
+fn parse_listing(payload: &str) -> Result<(Vec<ListedFile>, bool), ScrapperError> {
+    //Servers without listing support may answer with any page
+    if !payload.contains("<ListBucketResult") {
+        return Err(ScrapperError::ParseError("Response is not a bucket listing".to_string()));
+    }
+    let mut listed_files: Vec<ListedFile> = vec![];
+    for contents in get_tag_values(payload, "Contents") {
+        let key = get_tag_value(contents, "Key")?;
+        let size = get_tag_value(contents, "Size")?.parse::<u64>()?;
+        let last_modified = get_tag_value(contents, "LastModified")?;
+        let last_modified = DateTime::parse_from_rfc3339(last_modified)
+            .map_err(|err| ScrapperError::ParseError(format!("Invalid listing date {}: {}", last_modified, err)))?
+            .with_timezone(&Utc);
+        listed_files.push(ListedFile { key: key.to_string(), size, last_modified });
+    }
+    let truncated = get_tag_value(payload, "IsTruncated").is_ok_and(|value| value == "true");
+    Ok((listed_files, truncated))
+}
+
+fn get_tag_values<'a>(payload: &'a str, tag: &str) -> Vec<&'a str> {
+    let open_tag = format!("<{}>", tag);
+    let close_tag = format!("</{}>", tag);
+    let mut values: Vec<&str> = vec![];
+    let mut rest = payload;
+    while let Some(start) = rest.find(&open_tag) {
+        let content = &rest[start + open_tag.len()..];
+        match content.find(&close_tag) {
+            Some(end) => {
+                values.push(&content[..end]);
+                rest = &content[end + close_tag.len()..];
+            }
+            None => break,
+        }
+    }
+    values
+}
+
+fn get_tag_value<'a>(payload: &'a str, tag: &str) -> Result<&'a str, ScrapperError> {
+    get_tag_values(payload, tag).first().copied()
+        .ok_or(ScrapperError::ParseError(format!("Missing {} in bucket listing", tag)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Name>data.binance.vision</Name><Prefix>data/spot/monthly/klines/BTCUSDT/1m/</Prefix><Marker></Marker><MaxKeys>1000</MaxKeys><Delimiter>/</Delimiter><IsTruncated>true</IsTruncated><Contents><Key>data/spot/monthly/klines/BTCUSDT/1m/BTCUSDT-1m-2017-08.zip</Key><LastModified>2022-09-27T09:51:42.000Z</LastModified><ETag>&quot;d3a1&quot;</ETag><Size>1163124</Size><StorageClass>STANDARD</StorageClass></Contents><Contents><Key>data/spot/monthly/klines/BTCUSDT/1m/BTCUSDT-1m-2017-08.zip.CHECKSUM</Key><LastModified>2022-09-27T09:51:43.000Z</LastModified><ETag>&quot;a2f0&quot;</ETag><Size>105</Size><StorageClass>STANDARD</StorageClass></Contents></ListBucketResult>"#;
+
+    #[test]
+    fn test_parse_listing() {
+        let (files, truncated) = parse_listing(LISTING).unwrap();
+        assert!(truncated);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].get_file_name(), "BTCUSDT-1m-2017-08.zip");
+        assert_eq!(files[0].size, 1163124);
+        assert_eq!(files[1].last_modified.to_rfc3339(), "2022-09-27T09:51:43+00:00");
+    }
+
+    #[test]
+    fn test_parse_empty_listing() {
+        let (files, truncated) = parse_listing("<ListBucketResult><IsTruncated>false</IsTruncated></ListBucketResult>").unwrap();
+        assert!(!truncated);
+        assert!(files.is_empty());
+    }
+}
@@ -3,6 +3,12 @@ use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
+use crate::utils::asset_file::AssetFile;
+use crate::utils::data_type::DataType;
+use crate::utils::kline_schema::KlineSchema;
+use crate::utils::market::Market;
+use crate::utils::month_year::MonthYear;
+use crate::utils::timestamp_unit::{OUTPUT_TIMESTAMP_UNIT, TimestampUnit};
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct TimePeriod {
@@ -19,16 +25,26 @@ impl TimePeriod {
 
 #[derive(Serialize, Debug)]
 pub struct Manifest {
+    data_type: DataType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema: Option<KlineSchema>,
+    timestamp_unit: TimestampUnit,
     down_times: Vec<TimePeriod>,
     assets: HashMap<String, TimePeriod>,
+    //Holes in the monthly archives of an asset, its history goes on before and after them
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    missing_months: HashMap<String, Vec<MonthYear>>,
+    #[serde(skip_serializing)]
+    market: Market,
     #[serde(skip_serializing)]
     granularity: String,
 }
 
 
 impl Manifest {
-    pub fn new(granularity: &str) -> Manifest {
-        Manifest { down_times: vec![], assets: HashMap::new(), granularity: granularity.to_string() }
+    pub fn new(market: Market, data_type: DataType, granularity: &str, schema: KlineSchema) -> Manifest {
+        let schema = if data_type.has_granularity() { Some(schema) } else { None };
+        Manifest { data_type, schema, timestamp_unit: OUTPUT_TIMESTAMP_UNIT, down_times: vec![], assets: HashMap::new(), missing_months: HashMap::new(), market, granularity: granularity.to_string() }
     }
     pub fn add_down_time(&mut self, time_period: TimePeriod) {
         for down in &self.down_times {
@@ -41,13 +57,20 @@ impl Manifest {
     pub fn add_asset(&mut self, asset: &str, time_period: TimePeriod) {
         self.assets.insert(asset.to_string(), time_period);
     }
+    pub fn add_missing_months(&mut self, asset: &str, missing_months: Vec<MonthYear>) {
+        if !missing_months.is_empty() {
+            self.missing_months.insert(asset.to_string(), missing_months);
+        }
+    }
 
     pub fn save(&mut self) -> std::io::Result<()> {
         self.concat_down_times();
         let json = serde_json::to_string_pretty(&self)?;
-        let dir_path = format!("./binance_data/output/{}", self.granularity);
+        let dir_path = AssetFile::get_extract_directory_from_values(self.market, &self.data_type.get_dataset_name(&self.granularity));
         fs::create_dir_all(&dir_path)?;
-        let mut file = File::create(format!("{}/manifest.json", dir_path))?;
+        //Data types without a schema ignore it in their path
+        let schema = self.schema.unwrap_or(KlineSchema::Slim);
+        let mut file = File::create(AssetFile::get_manifest_path_from_values(self.market, self.data_type, &self.granularity, schema))?;
         file.write_all(json.as_bytes())?;
         Ok(())
     }
@@ -83,7 +106,12 @@ mod tests {
     #[test]
     fn test_no_overlap() {
         let mut m = Manifest {
+            data_type: DataType::Klines,
+            schema: Some(KlineSchema::Slim),
+            timestamp_unit: TimestampUnit::Milliseconds,
             assets: HashMap::new(),
+            missing_months: HashMap::new(),
+            market: Market::Spot,
             granularity: "1m".to_string(),
             down_times: vec![
                 TimePeriod { start: 1, end: 3 },
@@ -106,7 +134,12 @@ mod tests {
     #[test]
     fn test_overlap() {
         let mut m = Manifest {
+            data_type: DataType::Klines,
+            schema: Some(KlineSchema::Slim),
+            timestamp_unit: TimestampUnit::Milliseconds,
             assets: HashMap::new(),
+            missing_months: HashMap::new(),
+            market: Market::Spot,
             granularity: "1m".to_string(),
             down_times: vec![
                 TimePeriod { start: 1, end: 5 },
@@ -129,7 +162,12 @@ mod tests {
     #[test]
     fn test_contiguous_periods() {
         let mut m = Manifest {
+            data_type: DataType::Klines,
+            schema: Some(KlineSchema::Slim),
+            timestamp_unit: TimestampUnit::Milliseconds,
             assets: HashMap::new(),
+            missing_months: HashMap::new(),
+            market: Market::Spot,
             granularity: "1m".to_string(),
             down_times: vec![
                 TimePeriod { start: 1, end: 3 },
@@ -150,7 +188,12 @@ mod tests {
     #[test]
     fn test_contained_periods() {
         let mut m = Manifest {
+            data_type: DataType::Klines,
+            schema: Some(KlineSchema::Slim),
+            timestamp_unit: TimestampUnit::Milliseconds,
             assets: HashMap::new(),
+            missing_months: HashMap::new(),
+            market: Market::Spot,
             granularity: "1m".to_string(),
             down_times: vec![
                 TimePeriod { start: 1, end: 10 },
@@ -173,7 +216,12 @@ mod tests {
     #[test]
     fn test_single_period() {
         let mut m = Manifest {
+            data_type: DataType::Klines,
+            schema: Some(KlineSchema::Slim),
+            timestamp_unit: TimestampUnit::Milliseconds,
             assets: HashMap::new(),
+            missing_months: HashMap::new(),
+            market: Market::Spot,
             granularity: "1m".to_string(),
             down_times: vec![
                 TimePeriod { start: 1, end: 3 },
@@ -192,7 +240,12 @@ mod tests {
     #[test]
     fn test_empty_periods() {
         let mut m = Manifest {
+            data_type: DataType::Klines,
+            schema: Some(KlineSchema::Slim),
+            timestamp_unit: TimestampUnit::Milliseconds,
             assets: HashMap::new(),
+            missing_months: HashMap::new(),
+            market: Market::Spot,
             granularity: "1m".to_string(),
             down_times: vec![],
         };
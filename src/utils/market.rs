@@ -0,0 +1,86 @@
+use crate::utils::data_type::DataType;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Market {
+    Spot,
+    UsdM,
+    CoinM,
+    Options,
+}
+
+pub const MARKETS: [(&str, Market); 4] = [
+    ("spot", Market::Spot),
+    ("um", Market::UsdM),
+    ("cm", Market::CoinM),
+    ("option", Market::Options)];
+
+//Contract types scrapped on COIN-M, quarterly contracts are named {pair}_{delivery date}
+pub const COIN_M_CONTRACT_TYPES: [&str; 3] = ["PERPETUAL", "CURRENT_QUARTER", "NEXT_QUARTER"];
+
+impl Market {
+    pub fn from_name(name: &str) -> Option<Market> {
+        MARKETS.iter().find(|&&(key, _)| key == name).map(|&(_, market)| market)
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Market::Spot => "spot",
+            Market::UsdM => "um",
+            Market::CoinM => "cm",
+            Market::Options => "option",
+        }
+    }
+    pub fn get_url_path(&self) -> &'static str {
+        match self {
+            Market::Spot => "spot",
+            Market::UsdM => "futures/um",
+            Market::CoinM => "futures/cm",
+            Market::Options => "option",
+        }
+    }
+    pub fn get_api_host(&self) -> &'static str {
+        match self {
+            Market::Spot => "https://api.binance.com",
+            Market::UsdM => "https://fapi.binance.com",
+            Market::CoinM => "https://dapi.binance.com",
+            Market::Options => "https://eapi.binance.com",
+        }
+    }
+    //Paths differ between markets, a single api mirror can serve all of them
+    pub fn get_exchange_info_path(&self) -> &'static str {
+        match self {
+            Market::Spot => "/api/v3/exchangeInfo",
+            Market::UsdM => "/fapi/v1/exchangeInfo",
+            Market::CoinM => "/dapi/v1/exchangeInfo",
+            Market::Options => "/eapi/v1/exchangeInfo",
+        }
+    }
+    //Options are listed per contract, their underlyings are described in "optionContracts"
+    pub fn get_symbols_field(&self) -> &'static str {
+        match self {
+            Market::Options => "optionContracts",
+            _ => "symbols",
+        }
+    }
+    pub fn get_status_field(&self) -> &'static str {
+        match self {
+            Market::CoinM => "contractStatus",
+            _ => "status",
+        }
+    }
+    //COIN-M assets are already full symbols (BTCUSD_PERP, BTCUSD_240628)
+    pub fn get_symbol(&self, data_type: DataType, asset: &str, quote: &str) -> String {
+        match (self, data_type) {
+            (Market::CoinM, _) => asset.to_string(),
+            (Market::Options, DataType::BvolIndex) => format!("{}BVOL{}", asset, quote),
+            _ => format!("{}{}", asset, quote),
+        }
+    }
+    pub fn supports_granularity(&self, granularity: &str) -> bool {
+        match self {
+            Market::Spot | Market::Options => true,
+            //Futures archives do not provide 1s klines
+            Market::UsdM | Market::CoinM => granularity != "1s",
+        }
+    }
+}
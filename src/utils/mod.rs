@@ -5,3 +5,14 @@ pub mod manifest;
 pub mod process_data;
 pub mod month_year;
 pub mod start_dates;
+pub mod market;
+pub mod data_type;
+pub mod kline_schema;
+pub mod timestamp_unit;
+pub mod listing;
+pub mod endpoints;
+pub mod download_pool;
+pub mod retry_policy;
+pub mod download_client;
+pub mod rate_limiter;
+pub mod download_cache;
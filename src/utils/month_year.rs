@@ -1,6 +1,7 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MonthYear {
     month: u8,
     year: i32,
@@ -25,4 +26,49 @@ impl MonthYear {
         };
         format!("{}{}", prefix, self.month)
     }
+    pub fn next(&self) -> MonthYear {
+        if self.month == 12 {
+            MonthYear::new(1, self.year + 1)
+        } else {
+            MonthYear::new(self.month + 1, self.year)
+        }
+    }
+    pub fn previous(&self) -> MonthYear {
+        if self.month == 1 {
+            MonthYear::new(12, self.year - 1)
+        } else {
+            MonthYear::new(self.month - 1, self.year)
+        }
+    }
+    pub fn get_first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month as u32, 1).unwrap()
+    }
+}
+
+//Months between the first and last of the sorted months that are not part of them
+pub fn get_missing_months(months: &[MonthYear]) -> Vec<MonthYear> {
+    let mut missing_months: Vec<MonthYear> = vec![];
+    if let (Some(first_month), Some(last_month)) = (months.first(), months.last()) {
+        let mut month_year = first_month.clone();
+        while month_year != *last_month {
+            month_year = month_year.next();
+            if !months.contains(&month_year) {
+                missing_months.push(month_year.clone());
+            }
+        }
+    }
+    missing_months
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_months() {
+        let months = vec![MonthYear::new(11, 2020), MonthYear::new(2, 2021), MonthYear::new(3, 2021)];
+        let expected = vec![MonthYear::new(12, 2020), MonthYear::new(1, 2021)];
+        assert_eq!(get_missing_months(&months), expected);
+        assert!(get_missing_months(&[]).is_empty());
+    }
 }
@@ -1,21 +1,32 @@
-use chrono::{Datelike, Local};
+use chrono::{Datelike, Local, NaiveDate};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use crate::{BINANCE_BIRTH};
+use crate::utils::asset_file::AssetFile;
+use crate::utils::data_type::DataType;
+use crate::utils::kline_schema::KlineSchema;
+use crate::utils::market::Market;
 use crate::utils::month_year::MonthYear;
 
 #[derive(Clone)]
 pub struct ProcessData {
+    market: Market,
+    data_type: DataType,
     granularity: String,
-    asset: String,
+    schema: KlineSchema,
+    symbol: String,
     start: Option<MonthYear>,
     end: MonthYear,
+    delisted: bool,
+    monthly_files: Vec<MonthYear>,
+    missing_months: Vec<MonthYear>,
+    daily_files: Vec<NaiveDate>,
     progress_bar: Option<ProgressBar>,
 }
 
 impl ProcessData {
-    pub fn new(granularity: &str, asset: &str, start: Option<MonthYear>) -> ProcessData {
+    pub fn new(market: Market, data_type: DataType, granularity: &str, schema: KlineSchema, symbol: &str, start: Option<MonthYear>, delisted: bool) -> ProcessData {
         let end = get_end_date();
-        ProcessData { granularity: granularity.to_string(), start, asset: asset.to_string(), end, progress_bar: None }
+        ProcessData { market, data_type, granularity: granularity.to_string(), schema, start, symbol: symbol.to_string(), end, delisted, monthly_files: vec![], missing_months: vec![], daily_files: vec![], progress_bar: None }
     }
 
     pub fn init_progress_bar(&mut self, multi_progress: &MultiProgress) {
@@ -27,7 +38,7 @@ impl ProcessData {
 
         let pb = multi_progress.add(ProgressBar::new(bar_size as u64));
         pb.set_style(Self::get_progress_bar_style("white/grey"));
-        pb.set_prefix(format!("[{}]", self.asset.clone()));
+        pb.set_prefix(format!("[{}]", self.symbol.clone()));
         self.progress_bar = Some(pb);
     }
 
@@ -37,6 +48,16 @@ impl ProcessData {
             multi_progress.remove(pb);
         }
     }
+    //Once the archives are listed, the bar counts the planned files instead of the months since Binance birth
+    pub fn set_progress_bar_plan(&mut self, files_count: u64, planned_size: u64) {
+        if let Some(pb) = self.progress_bar.as_mut() {
+            pb.set_length(files_count);
+            pb.set_message(format!("{:.1} MB", planned_size as f64 / 1_000_000.0));
+        }
+    }
+    pub fn get_progress_bar(&self) -> Option<ProgressBar> {
+        self.progress_bar.clone()
+    }
     pub fn increment_progress_bar(&mut self) {
         if let Some(pb) = self.progress_bar.as_mut() {
             pb.inc(1);
@@ -52,11 +73,47 @@ impl ProcessData {
     pub fn get_end(&self) -> MonthYear {
         self.end.clone()
     }
-    pub fn get_asset(&self) -> String {
-        self.asset.clone()
+    pub fn set_end(&mut self, end: MonthYear) {
+        self.end = end;
+    }
+    pub fn is_delisted(&self) -> bool {
+        self.delisted
+    }
+    pub fn get_monthly_files(&self) -> Vec<MonthYear> {
+        self.monthly_files.clone()
+    }
+    pub fn add_monthly_file(&mut self, month_year: MonthYear) {
+        self.monthly_files.push(month_year);
+    }
+    pub fn get_missing_months(&self) -> Vec<MonthYear> {
+        self.missing_months.clone()
+    }
+    pub fn add_missing_month(&mut self, month_year: MonthYear) {
+        self.missing_months.push(month_year);
+    }
+    pub fn get_daily_files(&self) -> Vec<NaiveDate> {
+        self.daily_files.clone()
+    }
+    pub fn add_daily_file(&mut self, date: NaiveDate) {
+        self.daily_files.push(date);
+    }
+    pub fn get_data_type(&self) -> DataType {
+        self.data_type
+    }
+    pub fn get_schema(&self) -> KlineSchema {
+        self.schema
+    }
+    pub fn get_asset_file(&self, month_year: MonthYear) -> AssetFile {
+        AssetFile::new(self.market, self.data_type, &self.symbol, &self.granularity, self.schema, month_year)
+    }
+    pub fn get_daily_asset_file(&self, date: NaiveDate) -> AssetFile {
+        AssetFile::new_daily(self.market, self.data_type, &self.symbol, &self.granularity, self.schema, date)
+    }
+    pub fn get_listing_prefix(&self, daily: bool) -> String {
+        AssetFile::get_listing_prefix_from_values(self.market, self.data_type, &self.symbol, &self.granularity, daily)
     }
-    pub fn get_granularity(&self) -> String {
-        self.granularity.clone()
+    pub fn get_symbol(&self) -> String {
+        self.symbol.clone()
     }
     pub fn get_progress_bar_style(color: &str) -> ProgressStyle {
         let template = format!("{}{}{}", "{prefix:<9} {bar:50.", color, "} {percent:>4}% {msg:>4}");
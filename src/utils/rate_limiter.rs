@@ -0,0 +1,62 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+//Token bucket shared by every task, holding up to one second of tokens, consumers wait for the tokens they overdraw
+#[derive(Clone)]
+pub struct RateLimiter {
+    rate: Option<f64>,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    //No rate means no limit
+    pub fn new(rate: Option<u64>) -> RateLimiter {
+        let rate = rate.map(|rate| rate as f64);
+        RateLimiter { rate, bucket: Arc::new(Mutex::new(Bucket { tokens: rate.unwrap_or(0.0), last_refill: Instant::now() })) }
+    }
+
+    pub async fn acquire(&self, amount: u64) {
+        let Some(rate) = self.rate else {
+            return;
+        };
+        let missing_tokens = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let now = Instant::now();
+            bucket.tokens = (bucket.tokens + now.duration_since(bucket.last_refill).as_secs_f64() * rate).min(rate);
+            bucket.last_refill = now;
+            bucket.tokens -= amount as f64;
+            -bucket.tokens
+        };
+        if missing_tokens > 0.0 {
+            sleep(Duration::from_secs_f64(missing_tokens / rate)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_overdraw_waits() {
+        let limiter = RateLimiter::new(Some(100));
+        let start = Instant::now();
+        limiter.acquire(100).await;
+        limiter.acquire(20).await;
+        assert!(start.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited() {
+        let limiter = RateLimiter::new(None);
+        let start = Instant::now();
+        limiter.acquire(u64::MAX).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::future::Future;
+use std::time::Duration;
+use rand::Rng;
+use tokio::time::sleep;
+use crate::utils::errors::ScrapperError;
+
+const BASE_DELAY_MS: u64 = 500;
+const MAX_DELAY_MS: u64 = 30_000;
+
+//Retries transient request failures with an exponential backoff, the retries of every asset are counted together
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    retries: Arc<AtomicU64>,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> RetryPolicy {
+        RetryPolicy { max_retries, retries: Arc::new(AtomicU64::new(0)) }
+    }
+    pub fn get_max_retries(&self) -> u32 {
+        self.max_retries
+    }
+    pub fn get_retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    pub async fn run<T, F: FnMut() -> R, R: Future<Output = Result<T, ScrapperError>>>(&self, mut request: F) -> Result<T, ScrapperError> {
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Err(err) if err.is_transient() && attempt < self.max_retries => {
+                    sleep(get_backoff_delay(attempt)).await;
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+//Half of the delay is random, so that the downloads failing together don't retry together
+pub fn get_backoff_delay(attempt: u32) -> Duration {
+    let delay = BASE_DELAY_MS.saturating_mul(1 << attempt.min(16)).min(MAX_DELAY_MS);
+    Duration::from_millis(delay / 2 + rand::thread_rng().gen_range(0..=delay / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        for attempt in 0..20 {
+            let delay = get_backoff_delay(attempt).as_millis() as u64;
+            let max_delay = BASE_DELAY_MS.saturating_mul(1 << attempt.min(16)).min(MAX_DELAY_MS);
+            assert!(delay >= max_delay / 2 && delay <= max_delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_missing_data() {
+        let policy = RetryPolicy::new(3);
+        let mut calls = 0;
+        let result: Result<(), ScrapperError> = policy.run(|| {
+            calls += 1;
+            async { Err(ScrapperError::NoOnlineData) }
+        }).await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(policy.get_retries(), 0);
+    }
+}
@@ -3,36 +3,50 @@ use std::fs;
 use std::fs::File;
 use std::io::Write;
 use serde::{Deserialize, Serialize};
+use crate::utils::data_type::DataType;
+use crate::utils::market::Market;
 use crate::utils::month_year::MonthYear;
 
-const START_DATES_PATH: &str = "./start_dates.json";
+const START_DATES_PATH: &str = "./start_dates";
 
 #[derive(Serialize, Deserialize)]
 pub struct StartDates {
     start_dates: HashMap<String, MonthYear>,
+    #[serde(skip)]
+    path: String,
 }
 
 impl StartDates {
-    pub fn load() -> StartDates {
-        let start_dates = match fs::read_to_string(START_DATES_PATH) {
+    pub fn load(market: Market, data_type: DataType) -> StartDates {
+        let path = get_start_dates_path(market, data_type);
+        let start_dates = match fs::read_to_string(&path) {
             Ok(content) => serde_json::from_str(&content).unwrap(),
             Err(_) => HashMap::new(),
         };
-        StartDates { start_dates }
+        StartDates { start_dates, path }
     }
     pub fn save(&self) {
         let content = serde_json::to_string_pretty(&self.start_dates).unwrap();
-        let mut file = File::create(START_DATES_PATH).unwrap();
+        let mut file = File::create(&self.path).unwrap();
         file.write_all(content.as_bytes()).unwrap();
     }
 
-    pub fn get_start_date(&self, asset: &str) -> Option<MonthYear> {
-        if let Some(date) = self.start_dates.get(asset) {
+    pub fn get_start_date(&self, symbol: &str) -> Option<MonthYear> {
+        if let Some(date) = self.start_dates.get(symbol) {
             return Some(date.clone());
         }
         None
     }
-    pub fn set_start_date(&mut self, asset: &str, month_year: MonthYear) {
-        self.start_dates.insert(asset.to_string(), month_year);
+    pub fn set_start_date(&mut self, symbol: &str, month_year: MonthYear) {
+        self.start_dates.insert(symbol.to_string(), month_year);
+    }
+}
+
+//Data types start at different dates, klines keep the historical file names so existing start dates remain valid
+fn get_start_dates_path(market: Market, data_type: DataType) -> String {
+    match (market, data_type) {
+        (Market::Spot, DataType::Klines) => format!("{}.json", START_DATES_PATH),
+        (_, DataType::Klines) => format!("{}_{}.json", START_DATES_PATH, market.get_name()),
+        _ => format!("{}_{}_{}.json", START_DATES_PATH, market.get_name(), data_type.get_name()),
     }
 }
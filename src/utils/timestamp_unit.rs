@@ -0,0 +1,41 @@
+use serde::Serialize;
+
+//Millisecond timestamps stay below this bound until year 5138, microsecond ones are above it since 1973
+const MICROSECONDS_THRESHOLD: u64 = 100_000_000_000_000;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampUnit {
+    Milliseconds,
+    Microseconds,
+}
+
+//Unit of every timestamp written in the outputs
+pub const OUTPUT_TIMESTAMP_UNIT: TimestampUnit = TimestampUnit::Milliseconds;
+
+impl TimestampUnit {
+    //Spot archives switched to microseconds in 2025, the unit is detected on each file
+    pub fn detect(timestamp: u64) -> TimestampUnit {
+        if timestamp >= MICROSECONDS_THRESHOLD {
+            TimestampUnit::Microseconds
+        } else {
+            TimestampUnit::Milliseconds
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_milliseconds() {
+        assert_eq!(TimestampUnit::detect(1_735_689_600_000), TimestampUnit::Milliseconds);
+        assert_eq!(TimestampUnit::detect(1_502_942_400_000), TimestampUnit::Milliseconds);
+    }
+
+    #[test]
+    fn test_detect_microseconds() {
+        assert_eq!(TimestampUnit::detect(1_735_689_600_000_000), TimestampUnit::Microseconds);
+    }
+}